@@ -0,0 +1,161 @@
+// Times the fog overlay update both ways, without a window: the 6,400 per-cell sprites the
+// game used to recolour every frame, against the single mask texture (FogMask::sync).
+// Only the ECS side is measured; rendering 6,400 sprites instead of one costs more on top.
+//
+//   cargo run --release --example fog_bench
+
+use bevy::prelude::*;
+use std::time::{Duration, Instant};
+
+#[path = "../src/fog.rs"]
+mod fog;
+use fog::{FogMask, FOG_CLAIMED, FOG_UNCLAIMED};
+
+#[path = "../src/grid.rs"]
+#[allow(dead_code)] // Only the overlay's side of the grid is used here
+mod grid;
+use grid::{clear_grid_changes, GameGrid};
+
+// Stand-in for the game's settings module, which needs the rest of the game
+mod settings {
+    #[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+    pub enum RevealMode {
+        #[default]
+        Classic,
+        #[allow(dead_code)]
+        Progressive,
+    }
+}
+use settings::RevealMode;
+
+// Same playfield as the game
+const GRID_SIZE: usize = 80;
+const CELL_SIZE: f32 = 10.0;
+
+// Frames stepped for each overlay, and cells claimed per frame (about what a capture
+// of a few hundred cells every couple of seconds averages out to)
+const BENCH_FRAMES: usize = 1000;
+const CELLS_PER_FRAME: usize = 4;
+
+// One overlay sprite per grid cell, the way the fog was drawn before FogMask
+#[derive(Component)]
+struct CellSprite {
+    x: usize,
+    y: usize,
+}
+
+// Time spent in the overlay's update system over the timed frames
+#[derive(Resource, Default)]
+struct OverlayTime(Duration);
+
+// Claim the next few cells, row by row, as if the player were filling in the playfield
+fn claim_cells(mut grid: ResMut<GameGrid>, mut next: Local<usize>) {
+    for _ in 0..CELLS_PER_FRAME {
+        let cell = *next % (GRID_SIZE * GRID_SIZE);
+        grid.set_claimed(cell % GRID_SIZE, cell / GRID_SIZE, true);
+        *next += 1;
+    }
+}
+
+fn spawn_cell_sprites(mut commands: Commands) {
+    for y in 0..GRID_SIZE {
+        for x in 0..GRID_SIZE {
+            commands.spawn((
+                SpriteBundle {
+                    sprite: Sprite {
+                        color: Color::BLACK,
+                        custom_size: Some(Vec2::splat(CELL_SIZE)),
+                        ..default()
+                    },
+                    ..default()
+                },
+                CellSprite { x, y },
+            ));
+        }
+    }
+}
+
+// The old per-frame update: recolour every cell sprite
+fn update_cell_sprites(
+    mut sprite_query: Query<(&mut Sprite, &CellSprite)>,
+    grid: Res<GameGrid>,
+    mut overlay_time: ResMut<OverlayTime>,
+) {
+    let start = Instant::now();
+    let [r, g, b, _] = FOG_CLAIMED;
+    let [ur, ug, ub, _] = FOG_UNCLAIMED;
+    for (mut sprite, cell) in sprite_query.iter_mut() {
+        sprite.color = if grid.is_claimed(cell.x as i32, cell.y as i32) {
+            Color::srgb_u8(r, g, b)
+        } else {
+            Color::srgb_u8(ur, ug, ub)
+        };
+    }
+    overlay_time.0 += start.elapsed();
+}
+
+fn spawn_fog_mask(mut commands: Commands, mut images: ResMut<Assets<Image>>) {
+    commands.insert_resource(FogMask::new(&mut images));
+}
+
+fn sync_fog_mask(
+    mut fog_mask: ResMut<FogMask>,
+    grid: Res<GameGrid>,
+    mut images: ResMut<Assets<Image>>,
+    mut overlay_time: ResMut<OverlayTime>,
+) {
+    let start = Instant::now();
+    fog_mask.sync(&grid, &mut images, RevealMode::Classic);
+    overlay_time.0 += start.elapsed();
+}
+
+// Headless app with just the grid; the caller adds the overlay's systems
+fn bench_app() -> App {
+    let mut app = App::new();
+    app.add_plugins((MinimalPlugins, AssetPlugin::default()))
+        .init_asset::<Image>()
+        .insert_resource(GameGrid::new())
+        .init_resource::<OverlayTime>()
+        .add_systems(Last, clear_grid_changes);
+    app
+}
+
+// Step the app for BENCH_FRAMES frames (after one warm-up frame) and return the average
+// time per frame spent updating the overlay, and the average time for the whole frame
+fn time_frames(mut app: App) -> (Duration, Duration) {
+    app.update();
+    app.world_mut().resource_mut::<OverlayTime>().0 = Duration::ZERO;
+    let start = Instant::now();
+    for _ in 0..BENCH_FRAMES {
+        app.update();
+    }
+    let frames = BENCH_FRAMES as u32;
+    (app.world().resource::<OverlayTime>().0 / frames, start.elapsed() / frames)
+}
+
+fn main() {
+    println!(
+        "⏱️  Fog overlay benchmark: {} frames, {} cells claimed per frame",
+        BENCH_FRAMES, CELLS_PER_FRAME
+    );
+
+    let mut sprites = bench_app();
+    sprites
+        .add_systems(Startup, spawn_cell_sprites)
+        .add_systems(Update, (claim_cells, update_cell_sprites).chain());
+    let (before, before_frame) = time_frames(sprites);
+    println!(
+        "   {} cell sprites: {:?} per frame updating the overlay ({:?} per frame in all)",
+        GRID_SIZE * GRID_SIZE,
+        before,
+        before_frame
+    );
+
+    let mut mask = bench_app();
+    mask.add_systems(Startup, spawn_fog_mask)
+        .add_systems(Update, (claim_cells, sync_fog_mask).chain());
+    let (after, after_frame) = time_frames(mask);
+    println!("   Fog mask: {:?} per frame updating the overlay ({:?} per frame in all)", after, after_frame);
+
+    println!("   Overlay update {:.0}x faster", before.as_secs_f64() / after.as_secs_f64().max(1e-9));
+}
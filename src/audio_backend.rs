@@ -0,0 +1,226 @@
+use rodio::{OutputStream, OutputStreamHandle, Sink, Source};
+use std::cell::RefCell;
+use std::collections::BTreeMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+// Any sound a backend can play: decoded files and synth effects alike
+pub type Sound = Box<dyn Source<Item = f32> + Send>;
+
+// One sound started by a backend. Voices start paused; dropping one stops it.
+pub trait Voice: Send + Sync {
+    fn set_volume(&self, volume: f32);
+    fn play(&self);
+    fn pause(&self);
+    fn empty(&self) -> bool; // Finished playing
+}
+
+// Where the game's audio goes: the sound device, or nowhere when there isn't one
+pub trait AudioBackend {
+    fn start(&self, sound: Sound, label: &str) -> Result<Box<dyn Voice>, String>;
+
+    // Print anything worth knowing about the session's audio (called on exit)
+    fn report(&self) {}
+}
+
+// Plays through the default output device
+pub struct RodioBackend {
+    _stream: OutputStream, // Audio stops when this is dropped
+    handle: OutputStreamHandle,
+}
+
+impl RodioBackend {
+    pub fn open() -> Result<Self, String> {
+        let (stream, handle) = OutputStream::try_default().map_err(|err| err.to_string())?;
+        Ok(RodioBackend { _stream: stream, handle })
+    }
+}
+
+impl AudioBackend for RodioBackend {
+    fn start(&self, sound: Sound, _label: &str) -> Result<Box<dyn Voice>, String> {
+        let sink = Sink::try_new(&self.handle).map_err(|err| err.to_string())?;
+        sink.pause();
+        sink.append(sound);
+        Ok(Box::new(sink))
+    }
+}
+
+impl Voice for Sink {
+    fn set_volume(&self, volume: f32) {
+        Sink::set_volume(self, volume);
+    }
+
+    fn play(&self) {
+        Sink::play(self);
+    }
+
+    fn pause(&self) {
+        Sink::pause(self);
+    }
+
+    fn empty(&self) -> bool {
+        Sink::empty(self)
+    }
+}
+
+// Plays nothing, but keeps count of what would have played. Used with --no-audio
+// or when there's no sound device.
+#[derive(Default)]
+pub struct SilentBackend {
+    played: RefCell<BTreeMap<String, u32>>,
+}
+
+impl AudioBackend for SilentBackend {
+    fn start(&self, sound: Sound, label: &str) -> Result<Box<dyn Voice>, String> {
+        *self.played.borrow_mut().entry(label.to_string()).or_default() += 1;
+        Ok(Box::new(SilentVoice {
+            clock: Mutex::new(SilentClock::new(sound)),
+        }))
+    }
+
+    fn report(&self) {
+        let played = self.played.borrow();
+        if played.is_empty() {
+            return;
+        }
+        println!("🔇 Sounds played without audio output:");
+        for (label, count) in played.iter() {
+            println!("   {} x {}", count, label);
+        }
+    }
+}
+
+// Keeps time like a real voice so tracks still end and the playlist moves on
+struct SilentVoice {
+    clock: Mutex<SilentClock>,
+}
+
+struct SilentClock {
+    length: Option<Duration>, // Known up front, or found once the sound ran out
+    // Many sources (mp3 and ogg files, sounds built with from_iter) don't know their length
+    // up front, so their samples are pulled through as play time passes
+    sound: Option<Sound>,
+    position: f64, // Seconds of the sound pulled so far
+    played: Duration, // Play time before the last pause
+    resumed_at: Option<Instant>, // Some while playing
+}
+
+impl SilentClock {
+    fn new(sound: Sound) -> Self {
+        let length = sound.total_duration();
+        SilentClock {
+            length,
+            sound: if length.is_some() { None } else { Some(sound) },
+            position: 0.0,
+            played: Duration::ZERO,
+            resumed_at: None,
+        }
+    }
+
+    // Pull samples until the sound reaches `time`, noting its length if it runs out first
+    fn advance_to(&mut self, time: Duration) {
+        let Some(sound) = self.sound.as_mut() else {
+            return;
+        };
+        while self.position < time.as_secs_f64() {
+            let samples_per_second = sound.sample_rate() as f64 * sound.channels().max(1) as f64;
+            if sound.next().is_none() {
+                self.length = Some(Duration::from_secs_f64(self.position));
+                self.sound = None;
+                return;
+            }
+            self.position += 1.0 / samples_per_second.max(1.0);
+        }
+    }
+}
+
+impl Voice for SilentVoice {
+    fn set_volume(&self, _volume: f32) {}
+
+    fn play(&self) {
+        let mut clock = self.clock.lock().unwrap();
+        clock.resumed_at.get_or_insert_with(Instant::now);
+    }
+
+    fn pause(&self) {
+        let mut clock = self.clock.lock().unwrap();
+        if let Some(resumed_at) = clock.resumed_at.take() {
+            clock.played += resumed_at.elapsed();
+        }
+    }
+
+    fn empty(&self) -> bool {
+        let mut clock = self.clock.lock().unwrap();
+        let played = clock.played + clock.resumed_at.map(|at| at.elapsed()).unwrap_or_default();
+        clock.advance_to(played);
+        clock.length.is_some_and(|length| played >= length)
+    }
+}
+
+// The sound device, or the silent backend if audio is switched off or unavailable
+pub fn open_backend(no_audio: bool) -> Box<dyn AudioBackend> {
+    if no_audio {
+        println!("🔇 Audio switched off (--no-audio)");
+        return Box::<SilentBackend>::default();
+    }
+    match RodioBackend::open() {
+        Ok(backend) => Box::new(backend),
+        Err(err) => {
+            println!("⚠️  No audio output ({}), playing silently", err);
+            Box::<SilentBackend>::default()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::thread;
+
+    // A sound that doesn't report its length, like a decoded mp3
+    struct UnknownLength {
+        samples_left: usize,
+    }
+
+    impl Iterator for UnknownLength {
+        type Item = f32;
+
+        fn next(&mut self) -> Option<f32> {
+            self.samples_left = self.samples_left.checked_sub(1)?;
+            Some(0.0)
+        }
+    }
+
+    impl Source for UnknownLength {
+        fn current_frame_len(&self) -> Option<usize> {
+            None
+        }
+
+        fn channels(&self) -> u16 {
+            1
+        }
+
+        fn sample_rate(&self) -> u32 {
+            1000
+        }
+
+        fn total_duration(&self) -> Option<Duration> {
+            None
+        }
+    }
+
+    #[test]
+    fn silent_voice_of_unknown_length_finishes_after_playing() {
+        let backend = SilentBackend::default();
+        let voice = backend.start(Box::new(UnknownLength { samples_left: 20 }), "test").unwrap();
+
+        // Paused voices don't move on
+        thread::sleep(Duration::from_millis(40));
+        assert!(!voice.empty());
+
+        voice.play();
+        assert!(!voice.empty());
+        thread::sleep(Duration::from_millis(40));
+        assert!(voice.empty());
+    }
+}
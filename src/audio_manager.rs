@@ -1,84 +1,474 @@
-use rodio::{Sink, Decoder, Source, OutputStreamHandle};
+use rodio::{Decoder, Source};
 use rand::seq::SliceRandom;
-use std::fs::File;
-use std::io::{BufReader, Write, Read};
-use std::path::PathBuf;
+use rand::Rng;
+use std::fs::{self, File};
+use std::io::BufReader;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
 
-const CONFIG_FILE: &str = "mute_config.json";
+use crate::audio_backend::{AudioBackend, Sound, Voice};
+use crate::music::track_layers;
+use crate::settings::AudioSettings;
+
+// Seconds for a stem layer to fade fully in or out as the intensity changes
+const LAYER_FADE_SECONDS: f32 = 1.5;
 
 pub struct AudioManager {
-    sink: Sink,
+    backend: Box<dyn AudioBackend>,
+    sink: Option<Box<dyn Voice>>, // Current track, None until one plays
     muted: bool,
+    music_volume: f32, // Master volume times music volume
+    sfx_volume: f32, // Master volume times effects volume
     sound_files: Vec<PathBuf>,
-    stream_handle: OutputStreamHandle,
+    rejected: Vec<(PathBuf, Option<FileStamp>)>, // Files in the music folder that can't be played, so they're only reported once
+    playlist: Vec<PathBuf>, // Shuffled play order for the current pass through sound_files
+    position: usize, // Index of the current track in playlist
+    current_track: Option<PathBuf>,
+    fade_seconds: f32, // Crossfade length when the track changes, 0 = cut straight over
+    fade_in: f32, // How far the current track has faded in, 0.0 to 1.0
+    fading_out: Vec<(Box<dyn Voice>, f32)>, // Previous tracks still fading out, with their remaining volume
+    music_level: f32, // Overall music volume, eased towards music_level_target
+    music_level_target: f32,
+    layers: Vec<(Box<dyn Voice>, f32)>, // Extra stems of the current track, in step with it, with their level
+    intensity: f32, // 0.0 = main track only, 1.0 = every layer
 }
 
 impl AudioManager {
-    pub fn new(stream_handle: &rodio::OutputStreamHandle, sound_files: Vec<PathBuf>) -> Self {
-        let sink = Sink::try_new(stream_handle).unwrap();
-        let muted = Self::load_muted_state().unwrap_or(false);
+    pub fn new(backend: Box<dyn AudioBackend>, sound_files: Vec<PathBuf>, volume: &AudioSettings) -> Self {
         let mut manager = Self { 
-            sink, 
-            muted, 
-            sound_files,
-            stream_handle: stream_handle.clone(),
+            backend,
+            sink: None, 
+            muted: volume.muted, 
+            music_volume: volume.master_volume * volume.music_volume,
+            sfx_volume: volume.master_volume * volume.sfx_volume,
+            sound_files: Vec::new(),
+            rejected: Vec::new(),
+            playlist: Vec::new(),
+            position: 0,
+            current_track: None,
+            fade_seconds: 0.0,
+            fade_in: 1.0,
+            fading_out: Vec::new(),
+            music_level: 1.0,
+            music_level_target: 1.0,
+            layers: Vec::new(),
+            intensity: 0.0,
         };
-        manager.play_random_sound();
-        if manager.muted {
-            manager.sink.pause();
-        }
+        manager.sync_tracks(sound_files);
+        manager.shuffle_playlist();
+        manager.start_track();
         manager
     }
 
-    fn load_muted_state() -> Option<bool> {
-        let mut file = File::open(CONFIG_FILE).ok()?;
-        let mut content = String::new();
-        file.read_to_string(&mut content).ok()?;
-        Some(content.trim() == "true")
+    // Bring the track list in line with the files found in the music folder. New files that
+    // decode join the rest of this pass at random places; removed ones are dropped from it.
+    pub fn sync_tracks(&mut self, found: Vec<PathBuf>) {
+        // A rejected file is tried again once it's been replaced or finished copying
+        self.rejected.retain(|(file, stamp)| found.contains(file) && file_stamp(file) == *stamp);
+
+        // The current track keeps playing even if its file is gone
+        let mut index = 0;
+        let mut removed_before = 0;
+        let position = self.position;
+        self.sound_files.retain(|file| {
+            let keep = found.contains(file);
+            if !keep {
+                println!("🎵 Track removed: {}", file.display());
+            }
+            keep
+        });
+        let sound_files = &self.sound_files;
+        self.playlist.retain(|file| {
+            let keep = index == position || sound_files.contains(file);
+            if !keep && index < position {
+                removed_before += 1;
+            }
+            index += 1;
+            keep
+        });
+        self.position -= removed_before;
+
+        let mut rng = rand::thread_rng();
+        for file in found {
+            if self.sound_files.contains(&file) || self.rejected.iter().any(|(rejected, _)| *rejected == file) {
+                continue;
+            }
+            if let Err(err) = decode(&file) {
+                println!("⚠️  Skipping {}: {}", file.display(), err);
+                let stamp = file_stamp(&file);
+                self.rejected.push((file, stamp));
+                continue;
+            }
+            if self.current_track.is_some() {
+                println!("🎵 New track: {}", file.display());
+            }
+            let insert_at = rng.gen_range((self.position + 1).min(self.playlist.len())..=self.playlist.len());
+            self.playlist.insert(insert_at, file.clone());
+            self.sound_files.push(file);
+        }
     }
 
-    fn save_muted_state(&self) {
-        let mut file = File::create(CONFIG_FILE).expect("Failed to create config file");
-        let _ = file.write_all(if self.muted { b"true" } else { b"false" });
+    // New play order covering every track, not starting with the one that just played
+    fn shuffle_playlist(&mut self) {
+        self.playlist = self.sound_files.clone();
+        self.playlist.shuffle(&mut rand::thread_rng());
+        if self.playlist.len() > 1 && self.playlist.first() == self.current_track.as_ref() {
+            self.playlist.swap(0, 1);
+        }
+        self.position = 0;
     }
 
-    pub fn play_random_sound(&mut self) {
-        if self.sound_files.is_empty() { return; }
-        let chosen = self.sound_files.choose(&mut rand::thread_rng()).unwrap();
-        let file = BufReader::new(File::open(chosen).expect("Failed to open sound file"));
-        let source = Decoder::new(file).expect("Failed to decode").repeat_infinite();
-        self.sink.append(source);
+    // Play the track at the current playlist position from the start
+    fn start_track(&mut self) {
+        loop {
+            let Some(track) = self.playlist.get(self.position).cloned() else {
+                self.current_track = None;
+                return;
+            };
+
+            match self.open_track(&track) {
+                Ok(sink) => {
+                    let layer_paths = track_layers(&track);
+                    let layers: Vec<(Box<dyn Voice>, f32)> = layer_paths
+                        .iter()
+                        .enumerate()
+                        .filter_map(|(i, layer)| match self.open_track(layer) {
+                            // Start at the current intensity's level rather than fading in from silence
+                            Ok(layer_sink) => Some((layer_sink, self.layer_target(i, layer_paths.len()))),
+                            Err(err) => {
+                                println!("⚠️  Cannot play layer {}: {}", layer.display(), err);
+                                None
+                            }
+                        })
+                        .collect();
+
+                    let old_sink = self.sink.replace(sink);
+                    let old_layers = std::mem::replace(&mut self.layers, layers);
+                    if self.fade_seconds > 0.0 && !self.muted && self.current_track.is_some() {
+                        // Keep the old track playing while it fades out from wherever it was
+                        if let Some(old_sink) = old_sink {
+                            self.fading_out.push((old_sink, self.fade_in));
+                        }
+                        for (layer, level) in old_layers {
+                            self.fading_out.push((layer, self.fade_in * level));
+                        }
+                        self.fade_in = 0.0;
+                    } else {
+                        self.fade_in = 1.0; // Dropping the old sinks stops them
+                    }
+                    self.set_sink_volumes();
+
+                    // The track and its layers were opened paused so they start together
+                    if !self.muted {
+                        self.play_music();
+                    }
+                    println!("🎵 Now playing: {}", track.display());
+                    self.current_track = Some(track);
+                    return;
+                }
+                Err(err) => {
+                    // Drop unplayable tracks so they aren't tried again
+                    println!("⚠️  Cannot play {}: {}", track.display(), err);
+                    self.sound_files.retain(|file| *file != track);
+                    self.playlist.remove(self.position);
+                    if self.position >= self.playlist.len() {
+                        self.shuffle_playlist();
+                    }
+                }
+            }
+        }
+    }
+
+    // A paused voice with the file queued, or why it can't be played.
+    // Each track gets a fresh voice so the old one can fade out alongside it.
+    fn open_track(&self, path: &Path) -> Result<Box<dyn Voice>, String> {
+        self.backend.start(decode(path)?, &path.display().to_string())
+    }
+
+    // The current track and its layers together, so they stay in step
+    fn play_music(&self) {
+        self.sink.iter().chain(self.layers.iter().map(|(layer, _)| layer)).for_each(|voice| voice.play());
     }
 
-    pub fn toggle_mute(&mut self) {
-        self.muted = !self.muted;
+    fn pause_music(&self) {
+        self.sink.iter().chain(self.layers.iter().map(|(layer, _)| layer)).for_each(|voice| voice.pause());
+    }
+
+    // Level layer `index` of `count` should be at for the current intensity:
+    // the layers come in one after another as the intensity rises
+    fn layer_target(&self, index: usize, count: usize) -> f32 {
+        (self.intensity * count as f32 - index as f32).clamp(0.0, 1.0)
+    }
+
+    fn set_sink_volumes(&self) {
+        let volume = self.fade_in * self.music_level * self.music_volume;
+        if let Some(sink) = &self.sink {
+            sink.set_volume(volume);
+        }
+        for (layer, level) in &self.layers {
+            layer.set_volume(volume * level);
+        }
+        for (sink, remaining) in &self.fading_out {
+            sink.set_volume(remaining.max(0.0) * self.music_level * self.music_volume);
+        }
+    }
+
+    pub fn next_track(&mut self) {
+        self.position += 1;
+        if self.position >= self.playlist.len() {
+            self.shuffle_playlist();
+        }
+        self.start_track();
+    }
+
+    // Go back one track, or restart the current one at the start of the playlist
+    pub fn previous_track(&mut self) {
+        self.position = self.position.saturating_sub(1);
+        self.start_track();
+    }
+
+    // Crossfade to a particular track, unless it's already playing. The playlist
+    // carries on from there afterwards.
+    pub fn play_track(&mut self, track: &Path) {
+        if self.current_track.as_deref() == Some(track) {
+            return;
+        }
+        // Don't play it a second time later in this pass
+        if let Some(index) = self.playlist.iter().position(|file| file == track) {
+            if index > self.position {
+                self.playlist.remove(index);
+            }
+        }
+        let insert_at = (self.position + 1).min(self.playlist.len());
+        self.playlist.insert(insert_at, track.to_path_buf());
+        self.position = insert_at;
+        self.start_track();
+    }
+
+    pub fn set_fade_seconds(&mut self, seconds: f32) {
+        self.fade_seconds = seconds.max(0.0);
+    }
+
+    // Ease the music towards a fraction of full volume, e.g. to sit under the level complete display
+    pub fn set_music_level(&mut self, level: f32) {
+        self.music_level_target = level.clamp(0.0, 1.0);
+    }
+
+    // How intense the music should be, from 0.0 (main track only) to 1.0 (every stem layer).
+    // Layers fade towards the new level over LAYER_FADE_SECONDS.
+    pub fn set_intensity(&mut self, intensity: f32) {
+        self.intensity = intensity.clamp(0.0, 1.0);
+    }
+
+    // Advance fades, and move on to the next track once the current one has finished
+    // (call every frame with the frame time)
+    pub fn update(&mut self, delta_seconds: f32) {
+        let step = if self.fade_seconds > 0.0 { delta_seconds / self.fade_seconds } else { 1.0 };
+
+        self.music_level += (self.music_level_target - self.music_level).clamp(-step, step);
+        self.fade_in = (self.fade_in + step).min(1.0);
+
+        let layer_step = delta_seconds / LAYER_FADE_SECONDS;
+        let layer_count = self.layers.len();
+        for i in 0..layer_count {
+            let target = self.layer_target(i, layer_count);
+            let level = &mut self.layers[i].1;
+            *level += (target - *level).clamp(-layer_step, layer_step);
+        }
+
+        for (_, remaining) in &mut self.fading_out {
+            *remaining -= step;
+        }
+        // Dropping a sink stops it
+        self.fading_out.retain(|(sink, remaining)| *remaining > 0.0 && !sink.empty());
+        self.set_sink_volumes();
+
+        if self.current_track.is_some() && self.sink.as_ref().is_none_or(|sink| sink.empty()) {
+            self.next_track();
+        } else if self.current_track.is_none() && !self.playlist.is_empty() {
+            // Tracks have turned up since there was nothing to play
+            self.position = self.position.min(self.playlist.len() - 1);
+            self.start_track();
+        }
+    }
+
+    pub fn current_track(&self) -> Option<&Path> {
+        self.current_track.as_deref()
+    }
+
+    pub fn tracks(&self) -> &[PathBuf] {
+        &self.sound_files
+    }
+
+    // Apply the volume settings (call whenever they change). Music volume takes effect
+    // on the next update, effects volume on the next effect played.
+    pub fn set_volume(&mut self, volume: &AudioSettings) {
+        self.music_volume = volume.master_volume * volume.music_volume;
+        self.sfx_volume = volume.master_volume * volume.sfx_volume;
+        if volume.muted == self.muted {
+            return;
+        }
+        self.muted = volume.muted;
         if self.muted {
-            self.sink.pause();
+            self.pause_music();
+            self.fading_out.clear();
         } else {
-            self.sink.play();
+            self.play_music();
         }
-        self.save_muted_state();
     }
 
-    pub fn is_muted(&self) -> bool {
-        self.muted
+    // Start a one-shot sound effect at the effects volume (times `volume`) and hand back
+    // its voice so the caller can tell when it ends. Ok(None) while muted.
+    pub fn start_sound_effect(&self, sound_path: &Path, volume: f32) -> Result<Option<Box<dyn Voice>>, String> {
+        if self.muted {
+            return Ok(None); // Don't play sound effects when muted
+        }
+        self.start_effect(decode(sound_path)?, &sound_path.display().to_string(), volume).map(Some)
     }
 
-    pub fn play_sound_effect(&self, sound_path: &str) {
+    // Same as start_sound_effect, for a sound generated by the synth
+    pub fn start_synth_effect(&self, sound: Sound, label: &str, volume: f32) -> Result<Option<Box<dyn Voice>>, String> {
         if self.muted {
-            return; // Don't play sound effects when muted
-        }
-        
-        // Create a new sink for the sound effect (one-shot)
-        if let Ok(effect_sink) = Sink::try_new(&self.stream_handle) {
-            if let Ok(file) = File::open(sound_path) {
-                let file = BufReader::new(file);
-                if let Ok(source) = Decoder::new(file) {
-                    effect_sink.append(source);
-                    effect_sink.detach(); // Let it play independently and cleanup when done
-                }
-            }
+            return Ok(None);
         }
+        self.start_effect(sound, label, volume).map(Some)
+    }
+
+    fn start_effect(&self, sound: Sound, label: &str, volume: f32) -> Result<Box<dyn Voice>, String> {
+        let voice = self.backend.start(sound, label)?;
+        voice.set_volume(self.sfx_volume * volume);
+        voice.play();
+        Ok(voice)
     }
+
+    pub fn report(&self) {
+        self.backend.report();
+    }
+}
+
+// Size and modification time, to notice when a file has changed
+type FileStamp = (u64, SystemTime);
+
+fn file_stamp(path: &Path) -> Option<FileStamp> {
+    let metadata = fs::metadata(path).ok()?;
+    Some((metadata.len(), metadata.modified().ok()?))
+}
+
+fn decode(path: &Path) -> Result<Sound, String> {
+    let file = File::open(path).map_err(|err| err.to_string())?;
+    let decoder = Decoder::new(BufReader::new(file)).map_err(|err| err.to_string())?;
+    Ok(Box::new(decoder.convert_samples::<f32>()))
 }
 
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::audio_backend::SilentBackend;
+    use std::fs;
+    use std::thread;
+    use std::time::Duration;
+
+    // A fresh folder of silent 10ms WAV tracks named after `names`
+    fn write_tracks(test: &str, names: &[&str]) -> Vec<PathBuf> {
+        let dir = std::env::temp_dir().join(format!("strong-lines-{}-{}", test, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        names.iter().map(|name| write_wav(&dir.join(format!("{}.wav", name)))).collect()
+    }
+
+    // 80 samples of 16-bit mono silence at 8kHz
+    fn write_wav(path: &Path) -> PathBuf {
+        let data_len: u32 = 80 * 2;
+        let mut wav = Vec::new();
+        wav.extend_from_slice(b"RIFF");
+        wav.extend_from_slice(&(36 + data_len).to_le_bytes());
+        wav.extend_from_slice(b"WAVEfmt ");
+        wav.extend_from_slice(&16u32.to_le_bytes());
+        wav.extend_from_slice(&1u16.to_le_bytes()); // PCM
+        wav.extend_from_slice(&1u16.to_le_bytes()); // Mono
+        wav.extend_from_slice(&8000u32.to_le_bytes());
+        wav.extend_from_slice(&16000u32.to_le_bytes()); // Bytes per second
+        wav.extend_from_slice(&2u16.to_le_bytes()); // Bytes per frame
+        wav.extend_from_slice(&16u16.to_le_bytes());
+        wav.extend_from_slice(b"data");
+        wav.extend_from_slice(&data_len.to_le_bytes());
+        wav.resize(wav.len() + data_len as usize, 0);
+        fs::write(path, wav).unwrap();
+        path.to_path_buf()
+    }
+
+    fn silent_manager(tracks: Vec<PathBuf>) -> AudioManager {
+        AudioManager::new(Box::<SilentBackend>::default(), tracks, &AudioSettings::default())
+    }
+
+    #[test]
+    fn playlist_moves_on_when_a_track_ends() {
+        let tracks = write_tracks("advance", &["a", "b"]);
+        let mut manager = silent_manager(tracks);
+        let first = manager.current_track().unwrap().to_path_buf();
+
+        thread::sleep(Duration::from_millis(30));
+        manager.update(0.03);
+
+        assert_eq!(manager.position, 1);
+        assert_ne!(manager.current_track(), Some(first.as_path()));
+        assert!(manager.current_track().is_some());
+    }
+
+    #[test]
+    fn unplayable_track_is_skipped() {
+        let tracks = write_tracks("skip", &["a", "b", "c"]);
+        let mut manager = silent_manager(tracks);
+        let broken = manager.playlist[1].clone();
+        let after = manager.playlist[2].clone();
+        fs::remove_file(&broken).unwrap();
+
+        manager.next_track();
+
+        assert_eq!(manager.current_track(), Some(after.as_path()));
+        assert!(!manager.playlist.contains(&broken));
+        assert!(!manager.tracks().contains(&broken));
+    }
+
+    #[test]
+    fn rejected_file_is_retried_once_it_changes() {
+        let tracks = write_tracks("retry", &["a", "b"]);
+        fs::write(&tracks[1], b"not audio").unwrap();
+        let mut manager = silent_manager(tracks.clone());
+        assert_eq!(manager.tracks(), &tracks[..1]);
+
+        manager.sync_tracks(tracks.clone());
+        assert_eq!(manager.tracks().len(), 1);
+
+        write_wav(&tracks[1]);
+        manager.sync_tracks(tracks.clone());
+        assert_eq!(manager.tracks().len(), 2);
+    }
+
+    #[test]
+    fn sync_tracks_keeps_the_current_position() {
+        let tracks = write_tracks("sync", &["a", "b", "c", "d", "e"]);
+        let (new_track, tracks) = tracks.split_last().unwrap();
+        let mut manager = silent_manager(tracks.to_vec());
+        manager.next_track();
+        manager.next_track();
+        assert_eq!(manager.position, 2);
+        let current = manager.current_track().unwrap().to_path_buf();
+
+        // Drop one track before the current one and one after it
+        let removed = [manager.playlist[0].clone(), manager.playlist[3].clone()];
+        let found: Vec<PathBuf> = tracks.iter().filter(|track| !removed.contains(track)).cloned().collect();
+        manager.sync_tracks(found.clone());
+        assert_eq!(manager.position, 1);
+        assert_eq!(manager.playlist[manager.position], current);
+        assert_eq!(manager.playlist.len(), 2);
+
+        // A new track joins later in this pass
+        let mut found = found;
+        found.push(new_track.clone());
+        manager.sync_tracks(found);
+        assert_eq!(manager.playlist[manager.position], current);
+        let new_at = manager.playlist.iter().position(|track| track == new_track).unwrap();
+        assert!(new_at > manager.position);
+    }
+}
@@ -0,0 +1,291 @@
+use bevy::prelude::*;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError, Sender};
+use std::sync::{Arc, Mutex};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
+
+use crate::audio_backend::open_backend;
+use crate::audio_manager::AudioManager;
+use crate::music::{find_requested_track, list_music_files};
+use crate::settings::{AudioSettings, Settings, VolumeChannel};
+use crate::sound_bank::{PlaySfx, SoundBank};
+
+// How often the audio thread advances fades when no commands arrive
+const AUDIO_TICK: Duration = Duration::from_millis(10);
+// How often the music folder is checked for added or removed tracks
+const MUSIC_SCAN_INTERVAL: Duration = Duration::from_secs(5);
+
+// Set one of the volumes (0.0 to 1.0); saved to settings.toml
+#[derive(Event, Clone, Copy, Debug)]
+pub struct SetVolume {
+    pub channel: VolumeChannel,
+    pub volume: f32,
+}
+
+// Mute or unmute all audio; saved to settings.toml
+#[derive(Event, Clone, Copy, Debug)]
+pub struct ToggleMute;
+
+#[derive(Event, Clone, Copy, Debug)]
+pub struct NextTrack;
+
+#[derive(Event, Clone, Copy, Debug)]
+pub struct PreviousTrack;
+
+// Crossfade to the track a level's music request names, or one with that mood. The request
+// waits on the audio thread until there are tracks to pick from.
+#[derive(Event, Clone, Debug)]
+pub struct PlayRequested(pub String);
+
+// Ease the music towards a fraction of full volume, e.g. to duck it under menus
+#[derive(Event, Clone, Copy, Debug)]
+pub struct SetMusicLevel(pub f32);
+
+// How many stem layers play along, from 0.0 (main track only) to 1.0 (every layer)
+#[derive(Event, Clone, Copy, Debug)]
+pub struct SetIntensity(pub f32);
+
+// Pause (true) or resume the effects already playing
+#[derive(Event, Clone, Copy, Debug)]
+pub struct HoldEffects(pub bool);
+
+// Everything the audio thread can be asked to do
+enum AudioCommand {
+    PlaySfx(PlaySfx),
+    ApplySettings(AudioSettings),
+    SetFadeSeconds(f32),
+    SetMusicLevel(f32),
+    SetIntensity(f32),
+    HoldEffects(bool), // Pause (true) or resume the effects already playing
+    NextTrack,
+    PreviousTrack,
+    PlayRequested(String),
+    Shutdown,
+}
+
+// What the audio thread last reported back
+#[derive(Default)]
+struct AudioStatus {
+    current_track: Option<PathBuf>,
+    tracks: Vec<PathBuf>,
+}
+
+// The game's way to the audio thread. Systems send the events above, which are forwarded
+// to the thread, and read back what's playing from here.
+#[derive(Resource)]
+pub struct AudioHandle {
+    commands: Sender<AudioCommand>,
+    status: Arc<Mutex<AudioStatus>>,
+    thread: Mutex<Option<JoinHandle<()>>>,
+}
+
+impl AudioHandle {
+    fn send(&self, command: AudioCommand) {
+        // Fails only if the audio thread has gone, and then there's nothing to play on anyway
+        let _ = self.commands.send(command);
+    }
+
+    pub fn current_track(&self) -> Option<PathBuf> {
+        self.status.lock().ok()?.current_track.clone()
+    }
+
+    pub fn tracks(&self) -> Vec<PathBuf> {
+        self.status.lock().map(|status| status.tracks.clone()).unwrap_or_default()
+    }
+}
+
+// Plays the music and sound effects on a thread of its own, so no system needs
+// main-thread (NonSend) access to the audio device
+pub struct AudioPlugin {
+    pub no_audio: bool, // Play silently (--no-audio)
+    pub settings: AudioSettings, // Volumes to start with, so muted players hear nothing at all
+}
+
+impl Plugin for AudioPlugin {
+    fn build(&self, app: &mut App) {
+        let (commands, receiver) = mpsc::channel();
+        let status = Arc::new(Mutex::new(AudioStatus::default()));
+
+        let thread_status = Arc::clone(&status);
+        let no_audio = self.no_audio;
+        let settings = self.settings.clone();
+        let thread = thread::Builder::new()
+            .name("audio".to_string())
+            .spawn(move || run_audio_thread(no_audio, settings, receiver, thread_status))
+            .map_err(|err| println!("⚠️  Could not start the audio thread: {}", err))
+            .ok();
+
+        app.insert_resource(AudioHandle {
+            commands,
+            status,
+            thread: Mutex::new(thread),
+        })
+        .add_event::<PlaySfx>()
+        .add_event::<SetVolume>()
+        .add_event::<ToggleMute>()
+        .add_event::<NextTrack>()
+        .add_event::<PreviousTrack>()
+        .add_event::<PlayRequested>()
+        .add_event::<SetMusicLevel>()
+        .add_event::<SetIntensity>()
+        .add_event::<HoldEffects>()
+        .add_systems(Update, (apply_audio_controls, sync_audio_settings, forward_audio_events).chain())
+        .add_systems(Last, stop_audio_on_exit);
+    }
+}
+
+// Volume and mute changes go through the settings, so they're saved
+fn apply_audio_controls(
+    mut volume_events: EventReader<SetVolume>,
+    mut mute_events: EventReader<ToggleMute>,
+    mut settings: ResMut<Settings>,
+) {
+    let mut changed = false;
+    for event in volume_events.read() {
+        settings.audio.set_volume(event.channel, event.volume);
+        println!("🔊 {}: {:.0}%", event.channel.label(), settings.audio.volume(event.channel) * 100.0);
+        changed = true;
+    }
+    for _ in mute_events.read() {
+        settings.audio.muted = !settings.audio.muted;
+        if settings.audio.muted {
+            println!("🔇 Audio MUTED");
+        } else {
+            println!("🔊 Audio UNMUTED");
+        }
+        changed = true;
+    }
+    if changed {
+        settings.save();
+    }
+}
+
+fn sync_audio_settings(settings: Res<Settings>, audio: Res<AudioHandle>) {
+    if settings.is_changed() {
+        audio.send(AudioCommand::ApplySettings(settings.audio.clone()));
+        audio.send(AudioCommand::SetFadeSeconds(settings.music_fade_seconds));
+    }
+}
+
+fn forward_audio_events(
+    mut sfx_events: EventReader<PlaySfx>,
+    (mut next_events, mut previous_events, mut request_events): (
+        EventReader<NextTrack>,
+        EventReader<PreviousTrack>,
+        EventReader<PlayRequested>,
+    ),
+    (mut level_events, mut intensity_events, mut hold_events): (
+        EventReader<SetMusicLevel>,
+        EventReader<SetIntensity>,
+        EventReader<HoldEffects>,
+    ),
+    audio: Res<AudioHandle>,
+) {
+    for &sfx in sfx_events.read() {
+        audio.send(AudioCommand::PlaySfx(sfx));
+    }
+    for _ in next_events.read() {
+        audio.send(AudioCommand::NextTrack);
+    }
+    for _ in previous_events.read() {
+        audio.send(AudioCommand::PreviousTrack);
+    }
+    for PlayRequested(request) in request_events.read() {
+        audio.send(AudioCommand::PlayRequested(request.clone()));
+    }
+    for &SetMusicLevel(level) in level_events.read() {
+        audio.send(AudioCommand::SetMusicLevel(level));
+    }
+    for &SetIntensity(intensity) in intensity_events.read() {
+        audio.send(AudioCommand::SetIntensity(intensity));
+    }
+    for &HoldEffects(hold) in hold_events.read() {
+        audio.send(AudioCommand::HoldEffects(hold));
+    }
+}
+
+// Stop the audio thread when the game closes, letting it print its summary first
+fn stop_audio_on_exit(mut exit_events: EventReader<AppExit>, audio: Res<AudioHandle>) {
+    if exit_events.read().next().is_none() {
+        return;
+    }
+    audio.send(AudioCommand::Shutdown);
+    let thread = audio.thread.lock().ok().and_then(|mut thread| thread.take());
+    if let Some(thread) = thread {
+        let _ = thread.join();
+    }
+}
+
+// The audio thread: owns the output device, the music player and the sound bank
+fn run_audio_thread(
+    no_audio: bool,
+    settings: AudioSettings,
+    commands: Receiver<AudioCommand>,
+    status: Arc<Mutex<AudioStatus>>,
+) {
+    let mut manager = AudioManager::new(open_backend(no_audio), list_music_files(), &settings);
+    let mut bank = SoundBank::load();
+    let mut synth = settings.synth_effects;
+    let mut effects_held = false;
+    let mut requested: Option<String> = None; // Music request still waiting for tracks
+
+    let mut last_tick = Instant::now();
+    let mut last_scan = Instant::now();
+    loop {
+        // Wait for a command or the next tick, then take everything that's queued
+        let first = match commands.recv_timeout(AUDIO_TICK) {
+            Ok(command) => Some(command),
+            Err(RecvTimeoutError::Timeout) => None,
+            Err(RecvTimeoutError::Disconnected) => break,
+        };
+        for command in first.into_iter().chain(std::iter::from_fn(|| commands.try_recv().ok())) {
+            match command {
+                AudioCommand::PlaySfx(sfx) => bank.play(sfx, &manager, synth),
+                AudioCommand::ApplySettings(settings) => {
+                    synth = settings.synth_effects;
+                    manager.set_volume(&settings);
+                }
+                AudioCommand::SetFadeSeconds(seconds) => manager.set_fade_seconds(seconds),
+                AudioCommand::SetMusicLevel(level) => manager.set_music_level(level),
+                AudioCommand::SetIntensity(intensity) => manager.set_intensity(intensity),
+                AudioCommand::HoldEffects(hold) => effects_held = hold,
+                AudioCommand::NextTrack => manager.next_track(),
+                AudioCommand::PreviousTrack => manager.previous_track(),
+                AudioCommand::PlayRequested(request) => requested = Some(request),
+                AudioCommand::Shutdown => {
+                    manager.report();
+                    return;
+                }
+            }
+        }
+
+        manager.update(last_tick.elapsed().as_secs_f32());
+        last_tick = Instant::now();
+        bank.update(effects_held);
+
+        if last_scan.elapsed() >= MUSIC_SCAN_INTERVAL {
+            manager.sync_tracks(list_music_files());
+            last_scan = Instant::now();
+        }
+
+        if !manager.tracks().is_empty() {
+            if let Some(request) = requested.take() {
+                match find_requested_track(&request, manager.tracks()) {
+                    Some(track) => manager.play_track(&track),
+                    None => println!("⚠️  No music matches \"{}\"", request),
+                }
+            }
+        }
+
+        // Let the game know what's playing
+        if let Ok(mut status) = status.lock() {
+            if status.current_track.as_deref() != manager.current_track() {
+                status.current_track = manager.current_track().map(Path::to_path_buf);
+            }
+            if status.tracks != manager.tracks() {
+                status.tracks = manager.tracks().to_vec();
+            }
+        }
+    }
+}
@@ -0,0 +1,158 @@
+use bevy::prelude::*;
+use bevy::render::render_resource::TextureFormat;
+
+use crate::settings::{FitMode, Settings};
+use crate::{BackgroundImage, BackgroundSprite, WINDOW_HEIGHT, WINDOW_WIDTH};
+
+// Sample every Nth pixel when scoring crop windows for smart crop
+const SMART_CROP_SAMPLE_STEP: usize = 4;
+// How strongly smart crop prefers the centre when detail is similar (0 = not at all)
+const SMART_CROP_CENTRE_BIAS: f32 = 0.1;
+
+// Marks a background sprite whose size still has to be fitted once its image has loaded
+#[derive(Component)]
+pub struct PendingFit;
+
+// Where the background picture ended up: the sprite's size in world units and the
+// region of the source image it shows
+#[derive(Clone, Copy, Debug)]
+pub struct BackgroundLayout {
+    pub size: Vec2,
+    pub rect: Rect,
+}
+
+impl BackgroundLayout {
+    // Source pixel under a world position, or None if the picture doesn't cover it
+    pub fn image_pixel_at(&self, world: Vec2) -> Option<UVec2> {
+        let uv = (world + self.size / 2.0) / self.size;
+        if !(0.0..1.0).contains(&uv.x) || !(0.0..1.0).contains(&uv.y) {
+            return None;
+        }
+        // Image rows run top to bottom while world y runs bottom to top
+        let pixel = self.rect.min + Vec2::new(uv.x, 1.0 - uv.y) * self.rect.size();
+        Some(pixel.as_uvec2())
+    }
+}
+
+type BackgroundSpriteQuery<'w, 's> =
+    Query<'w, 's, (Entity, &'static Handle<Image>, &'static mut Sprite, Has<PendingFit>), With<BackgroundSprite>>;
+
+// Size the background sprites to the chosen fit mode once their image dimensions are known,
+// and again whenever the fit mode setting changes
+pub fn fit_background_sprites(
+    mut commands: Commands,
+    mut background_query: BackgroundSpriteQuery,
+    images: Res<Assets<Image>>,
+    settings: Res<Settings>,
+    mut bg_image: ResMut<BackgroundImage>,
+) {
+    for (entity, handle, mut sprite, pending) in background_query.iter_mut() {
+        if !pending && !settings.is_changed() {
+            continue;
+        }
+        let Some(image) = images.get(handle) else {
+            continue; // Still loading
+        };
+
+        let layout = compute_layout(image, settings.fit_mode);
+        sprite.custom_size = Some(layout.size);
+        sprite.rect = Some(layout.rect);
+        if *handle == bg_image.handle {
+            bg_image.layout = Some(layout);
+        }
+        if pending {
+            commands.entity(entity).remove::<PendingFit>();
+        }
+    }
+}
+
+fn compute_layout(image: &Image, mode: FitMode) -> BackgroundLayout {
+    let image_size = image.size().as_vec2();
+    let window = Vec2::new(WINDOW_WIDTH, WINDOW_HEIGHT);
+    let full = Rect::from_corners(Vec2::ZERO, image_size);
+
+    match mode {
+        FitMode::Stretch => BackgroundLayout { size: window, rect: full },
+        FitMode::Contain => {
+            // Shrink until the whole picture fits; the clear colour shows as letterbox bars
+            let scale = (window / image_size).min_element();
+            BackgroundLayout {
+                size: image_size * scale,
+                rect: full,
+            }
+        }
+        FitMode::Cover | FitMode::SmartCrop => {
+            // Fill the window and crop whatever overhangs
+            let scale = (window / image_size).max_element();
+            let crop = window / scale;
+            let slack = image_size - crop;
+            let offset = if mode == FitMode::SmartCrop {
+                smart_crop_offset(image, crop).unwrap_or(slack / 2.0)
+            } else {
+                slack / 2.0
+            };
+            BackgroundLayout {
+                size: window,
+                rect: Rect::from_corners(offset, offset + crop),
+            }
+        }
+    }
+}
+
+// Choose the crop position that keeps the most detail (sum of luminance gradients).
+// Only the overhanging axis is searched. Returns None for pixel formats we can't read.
+fn smart_crop_offset(image: &Image, crop: Vec2) -> Option<Vec2> {
+    if !matches!(
+        image.texture_descriptor.format,
+        TextureFormat::Rgba8UnormSrgb | TextureFormat::Rgba8Unorm
+    ) {
+        return None;
+    }
+
+    let width = image.width() as usize;
+    let height = image.height() as usize;
+    let luminance = |x: usize, y: usize| {
+        let i = (y * width + x) * 4;
+        let data = &image.data;
+        0.299 * data[i] as f32 + 0.587 * data[i + 1] as f32 + 0.114 * data[i + 2] as f32
+    };
+
+    let horizontal = crop.x < width as f32 - 1.0;
+    let (length, across) = if horizontal { (width, height) } else { (height, width) };
+    let window = (if horizontal { crop.x } else { crop.y }) as usize;
+    if window == 0 || window >= length {
+        return None;
+    }
+
+    // Detail per column (or row) along the axis being cropped
+    let mut energy = vec![0.0; length];
+    for along in (0..length - 1).step_by(SMART_CROP_SAMPLE_STEP) {
+        for other in (0..across - 1).step_by(SMART_CROP_SAMPLE_STEP) {
+            let (x, y) = if horizontal { (along, other) } else { (other, along) };
+            let here = luminance(x, y);
+            energy[along] += (luminance(x + 1, y) - here).abs() + (luminance(x, y + 1) - here).abs();
+        }
+    }
+
+    let mut prefix = vec![0.0; length + 1];
+    for i in 0..length {
+        prefix[i + 1] = prefix[i] + energy[i];
+    }
+    let max_start = length - window;
+    let centre = max_start as f32 / 2.0;
+    let best = (0..=max_start)
+        .map(|start| {
+            let detail = prefix[start + window] - prefix[start];
+            let off_centre = (start as f32 - centre).abs() / centre.max(1.0);
+            (start, detail * (1.0 - SMART_CROP_CENTRE_BIAS * off_centre))
+        })
+        .max_by(|a, b| a.1.total_cmp(&b.1))
+        .map(|(start, _)| start as f32)?;
+
+    let slack = Vec2::new(width as f32, height as f32) - crop;
+    Some(if horizontal {
+        Vec2::new(best, slack.y / 2.0)
+    } else {
+        Vec2::new(slack.x / 2.0, best)
+    })
+}
@@ -0,0 +1,247 @@
+use bevy::prelude::*;
+use serde::Deserialize;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::packs::{split_pack_path, ImagePack};
+use crate::sound_bank::{GameSound, PlaySfx};
+use crate::{GamePhase, PictureSource};
+
+// Credits screen lines visible at once
+const CREDITS_PAGE_LINES: usize = 14;
+
+// Who made a picture or music track and under what terms. Read from a sidecar file
+// next to the asset (cat.jpg -> cat.toml), a pack manifest, or guessed from the file name.
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct Credit {
+    pub title: Option<String>,
+    pub credit: Option<String>, // e.g. "Photo by Jane Doe"
+    pub licence: Option<String>,
+    pub source: Option<String>, // Where it came from, e.g. a website
+}
+
+impl Credit {
+    // One line like "Lion at dusk - Photo by Jane Doe | pixabay.com | CC BY 4.0"
+    pub fn summary(&self) -> String {
+        let mut line = self.title.clone().unwrap_or_else(|| "Untitled".to_string());
+        if let Some(credit) = &self.credit {
+            line.push_str(&format!(" - {}", credit));
+        }
+        for detail in [&self.source, &self.licence].into_iter().flatten() {
+            line.push_str(&format!(" | {}", detail));
+        }
+        line
+    }
+
+    // Fill in anything the sidecar left out from a guessed credit
+    fn or(self, fallback: Credit) -> Credit {
+        Credit {
+            title: self.title.or(fallback.title),
+            credit: self.credit.or(fallback.credit),
+            licence: self.licence.or(fallback.licence),
+            source: self.source.or(fallback.source),
+        }
+    }
+}
+
+// Sidecar metadata file for an asset: same folder and name, .toml extension
+fn sidecar_credit(path: &Path) -> Option<Credit> {
+    let content = fs::read_to_string(path.with_extension("toml")).ok()?;
+    match toml::from_str(&content) {
+        Ok(credit) => Some(credit),
+        Err(err) => {
+            println!("⚠️  Ignoring invalid credits for {}: {}", path.display(), err);
+            None
+        }
+    }
+}
+
+// Pixabay downloads are named "<words>-<image id>_<width>", e.g. "cat-323262_1920"
+fn pixabay_credit(stem: &str) -> Option<Credit> {
+    let (words, rest) = stem.rsplit_once('-')?;
+    let (id, width) = rest.split_once('_')?;
+    if id.is_empty() || !id.chars().chain(width.chars()).all(|c| c.is_ascii_digit()) {
+        return None;
+    }
+    Some(Credit {
+        title: Some(capitalise(&words.replace('-', " "))),
+        source: Some(format!("Pixabay #{}", id)),
+        ..default()
+    })
+}
+
+// Tracks from freetouse.com and similar are named "Artist - Title (site)"
+fn track_name_credit(stem: &str) -> Credit {
+    let (name, source) = match stem.rsplit_once(" (") {
+        Some((name, site)) if site.ends_with(')') => (name, Some(site.trim_end_matches(')').to_string())),
+        _ => (stem, None),
+    };
+    let (artist, title) = match name.split_once(" - ") {
+        Some((artist, title)) => (Some(artist.trim().to_string()), title.trim()),
+        None => (None, name.trim()),
+    };
+    Credit {
+        title: Some(title.to_string()),
+        credit: artist,
+        source,
+        ..default()
+    }
+}
+
+fn capitalise(text: &str) -> String {
+    let mut chars = text.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => String::new(),
+    }
+}
+
+fn file_stem(path: &Path) -> String {
+    path.file_stem().unwrap_or_default().to_string_lossy().to_string()
+}
+
+pub fn picture_credit(picture: &PictureSource, packs: &[ImagePack]) -> Credit {
+    match picture {
+        PictureSource::File(path) => file_picture_credit(path, packs),
+        PictureSource::Generated { style, seed } => Credit {
+            title: Some(format!("Generated {} art", style.label())),
+            credit: Some("Painted by the game".to_string()),
+            source: Some(format!("seed {:x}", seed)),
+            ..default()
+        },
+        PictureSource::Fallback { .. } => Credit {
+            title: Some("Fallback picture".to_string()),
+            credit: Some("Painted by the game".to_string()),
+            ..default()
+        },
+    }
+}
+
+fn file_picture_credit(picture_path: &str, packs: &[ImagePack]) -> Credit {
+    // Pack pictures are credited by the pack's manifest
+    if let Some((zip_name, entry)) = split_pack_path(picture_path) {
+        let stem = file_stem(Path::new(entry));
+        let Some(pack) = packs.iter().find(|pack| pack.file_name == zip_name) else {
+            return Credit {
+                title: Some(stem),
+                ..default()
+            };
+        };
+        let info = pack.image_info(entry);
+        let author = (!pack.manifest.author.is_empty()).then(|| pack.manifest.author.clone());
+        let licence = (!pack.manifest.licence.is_empty()).then(|| pack.manifest.licence.clone());
+        return Credit {
+            title: info.and_then(|info| info.title.clone()).or(Some(stem)),
+            credit: info.and_then(|info| info.credit.clone()).or(author),
+            licence,
+            source: Some(format!("{} pack", pack.manifest.name)),
+        };
+    }
+
+    let path = Path::new("assets").join(picture_path);
+    let stem = file_stem(&path);
+    let guessed = pixabay_credit(&stem).unwrap_or(Credit {
+        title: Some(stem),
+        ..default()
+    });
+    match sidecar_credit(&path) {
+        Some(credit) => credit.or(guessed),
+        None => guessed,
+    }
+}
+
+pub fn track_credit(path: &Path) -> Credit {
+    let guessed = track_name_credit(&file_stem(path));
+    match sidecar_credit(path) {
+        Some(credit) => credit.or(guessed),
+        None => guessed,
+    }
+}
+
+// Credits shown for the level that was just completed
+#[derive(Resource, Default)]
+pub struct LevelCredits {
+    pub picture: Option<Credit>,
+    pub track: Option<Credit>,
+}
+
+#[derive(Resource, Default)]
+pub struct CreditsScreen {
+    lines: Vec<String>,
+    scroll: usize,
+}
+
+impl CreditsScreen {
+    // Gather the credits for every picture and track the game can currently use
+    pub fn open(&mut self, pictures: &[String], packs: &[ImagePack], tracks: &[PathBuf]) {
+        // Every asset is listed with its licence, or a note that none is recorded
+        let listing = |credit: Credit| {
+            let mut line = credit.summary();
+            if credit.licence.is_none() {
+                line.push_str(" | licence not recorded");
+            }
+            line
+        };
+
+        let mut lines = vec!["PICTURES".to_string()];
+        for picture in pictures {
+            lines.push(listing(file_picture_credit(picture, packs)));
+        }
+        lines.push("Generated pictures - painted by the game".to_string());
+
+        lines.push(String::new());
+        lines.push("MUSIC".to_string());
+        if tracks.is_empty() {
+            lines.push("No music installed".to_string());
+        }
+        for track in tracks {
+            lines.push(listing(track_credit(track)));
+        }
+
+        self.lines = lines;
+        self.scroll = 0;
+    }
+}
+
+pub fn handle_credits_screen(
+    keyboard: Res<ButtonInput<KeyCode>>,
+    mut game_phase: ResMut<GamePhase>,
+    mut screen: ResMut<CreditsScreen>,
+    mut sounds: EventWriter<PlaySfx>,
+) {
+    if *game_phase != GamePhase::Credits {
+        return;
+    }
+
+    let max_scroll = screen.lines.len().saturating_sub(CREDITS_PAGE_LINES);
+    let scroll = screen.scroll;
+    if keyboard.just_pressed(KeyCode::ArrowUp) || keyboard.just_pressed(KeyCode::KeyW) {
+        screen.scroll = screen.scroll.saturating_sub(1);
+    }
+    if keyboard.just_pressed(KeyCode::ArrowDown) || keyboard.just_pressed(KeyCode::KeyS) {
+        screen.scroll = (screen.scroll + 1).min(max_scroll);
+    }
+    if screen.scroll != scroll {
+        sounds.send(GameSound::MenuMove.into());
+    }
+
+    if keyboard.just_pressed(KeyCode::Escape) || keyboard.just_pressed(KeyCode::Enter) {
+        *game_phase = GamePhase::HighScoreScreen;
+    }
+}
+
+pub fn credits_screen_text(screen: &CreditsScreen) -> String {
+    let mut display = String::from("=== CREDITS ===\n\n");
+    for line in screen.lines.iter().skip(screen.scroll).take(CREDITS_PAGE_LINES) {
+        display.push_str(line);
+        display.push('\n');
+    }
+    if screen.lines.len() > CREDITS_PAGE_LINES {
+        display.push_str("\nUP/DOWN: Scroll | ");
+    } else {
+        display.push('\n');
+    }
+    display.push_str("ESC or ENTER: Back");
+    display
+}
@@ -0,0 +1,79 @@
+use bevy::prelude::*;
+use bevy::render::render_asset::RenderAssetUsages;
+use bevy::render::render_resource::{Extent3d, TextureDimension, TextureFormat};
+use bevy::render::texture::ImageSampler;
+
+use crate::grid::GameGrid;
+use crate::settings::RevealMode;
+use crate::GRID_SIZE;
+
+// Fog overlay pixel colours (sRGB bytes)
+pub const FOG_UNCLAIMED: [u8; 4] = [0, 0, 0, 255]; // Pure black to hide image
+pub const FOG_CLAIMED: [u8; 4] = [38, 38, 38, 255]; // Claimed but not revealed yet - darker gray
+pub const FOG_REVEALED: [u8; 4] = [0, 0, 0, 0]; // Claimed in progressive mode - picture shows through
+
+#[derive(Resource)]
+pub struct FogMask {
+    pub handle: Handle<Image>,
+    mode: RevealMode, // Reveal mode the current pixels were written with
+}
+
+impl FogMask {
+    pub fn new(images: &mut Assets<Image>) -> Self {
+        let mut image = Image::new_fill(
+            Extent3d {
+                width: GRID_SIZE as u32,
+                height: GRID_SIZE as u32,
+                depth_or_array_layers: 1,
+            },
+            TextureDimension::D2,
+            &FOG_UNCLAIMED,
+            TextureFormat::Rgba8UnormSrgb,
+            RenderAssetUsages::default(),
+        );
+        // Keep cell edges crisp when the 80x80 texture is stretched over the playfield
+        image.sampler = ImageSampler::nearest();
+
+        FogMask {
+            handle: images.add(image),
+            mode: RevealMode::default(),
+        }
+    }
+
+    // Write the colour of every cell the grid reports as changed this frame,
+    // or of every cell if the reveal mode changed since the last sync
+    pub fn sync(&mut self, grid: &GameGrid, images: &mut Assets<Image>, mode: RevealMode) {
+        let mode_changed = mode != self.mode;
+        // Only borrow the image mutably when something changed, as that triggers a GPU upload
+        if !grid.has_changes() && !mode_changed {
+            return;
+        }
+        let Some(image) = images.get_mut(&self.handle) else {
+            return;
+        };
+        self.mode = mode;
+
+        let claimed_color = match mode {
+            RevealMode::Classic => &FOG_CLAIMED,
+            RevealMode::Progressive => &FOG_REVEALED,
+        };
+        let mut write_cell = |x: usize, y: usize| {
+            let claimed = grid.is_claimed(x as i32, y as i32);
+            // Texture rows run top to bottom while grid rows run bottom to top
+            let pixel = ((GRID_SIZE - 1 - y) * GRID_SIZE + x) * 4;
+            image.data[pixel..pixel + 4].copy_from_slice(if claimed { claimed_color } else { &FOG_UNCLAIMED });
+        };
+
+        if mode_changed {
+            for y in 0..GRID_SIZE {
+                for x in 0..GRID_SIZE {
+                    write_cell(x, y);
+                }
+            }
+        } else {
+            for (x, y) in grid.changed_cells() {
+                write_cell(x, y);
+            }
+        }
+    }
+}
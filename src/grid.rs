@@ -0,0 +1,133 @@
+use bevy::prelude::*;
+use std::collections::HashSet;
+
+use crate::GRID_SIZE;
+
+const TOTAL_CELLS: usize = GRID_SIZE * GRID_SIZE;
+const WORD_BITS: usize = 64;
+const WORD_COUNT: usize = TOTAL_CELLS.div_ceil(WORD_BITS);
+
+// Claimed territory stored as a bitset (one bit per cell, row-major from the bottom row)
+// plus a running claimed count and the set of cells changed since the last frame ended.
+#[derive(Resource)]
+pub struct GameGrid {
+    claimed: [u64; WORD_COUNT],
+    claimed_count: usize,
+    dirty: HashSet<(usize, usize)>,
+    all_dirty: bool, // Set by reset() - every cell must be treated as changed
+    pub drawing_path: Vec<(i32, i32)>,
+    pub last_capture: Option<Vec2>, // Centre (in cells) of the most recently captured area
+}
+
+impl GameGrid {
+    pub fn new() -> Self {
+        let mut grid = GameGrid {
+            claimed: [0; WORD_COUNT],
+            claimed_count: 0,
+            dirty: HashSet::new(),
+            all_dirty: true,
+            drawing_path: Vec::new(),
+            last_capture: None,
+        };
+        grid.reset();
+        grid
+    }
+
+    // Clear all territory except the edges (safe starting zone)
+    pub fn reset(&mut self) {
+        self.claimed = [0; WORD_COUNT];
+        self.claimed_count = 0;
+        for i in 0..GRID_SIZE {
+            self.set_claimed(i, 0, true); // Bottom edge
+            self.set_claimed(i, GRID_SIZE - 1, true); // Top edge
+            self.set_claimed(0, i, true); // Left edge
+            self.set_claimed(GRID_SIZE - 1, i, true); // Right edge
+        }
+        self.drawing_path.clear();
+        self.last_capture = None;
+        self.dirty.clear();
+        self.all_dirty = true;
+    }
+
+    fn bit(x: usize, y: usize) -> (usize, u64) {
+        let index = y * GRID_SIZE + x;
+        (index / WORD_BITS, 1 << (index % WORD_BITS))
+    }
+
+    fn get(&self, x: usize, y: usize) -> bool {
+        let (word, mask) = Self::bit(x, y);
+        self.claimed[word] & mask != 0
+    }
+
+    // Set a cell's claimed state, keeping the count and dirty set in step.
+    // Returns true if the cell actually changed.
+    pub fn set_claimed(&mut self, x: usize, y: usize, claimed: bool) -> bool {
+        if self.get(x, y) == claimed {
+            return false;
+        }
+        let (word, mask) = Self::bit(x, y);
+        if claimed {
+            self.claimed[word] |= mask;
+            self.claimed_count += 1;
+        } else {
+            self.claimed[word] &= !mask;
+            self.claimed_count -= 1;
+        }
+        if !self.all_dirty {
+            self.dirty.insert((x, y));
+        }
+        true
+    }
+
+    pub fn is_claimed(&self, x: i32, y: i32) -> bool {
+        if x < 0 || x >= GRID_SIZE as i32 || y < 0 || y >= GRID_SIZE as i32 {
+            return true; // Outside the grid counts as solid
+        }
+        self.get(x as usize, y as usize)
+    }
+
+    // A cell an enemy may occupy: unclaimed and not part of the line being drawn
+    pub fn is_free(&self, x: i32, y: i32) -> bool {
+        !self.is_claimed(x, y) && !self.drawing_path.contains(&(x, y))
+    }
+
+    pub fn claimed_percentage(&self) -> f32 {
+        self.claimed_count as f32 / TOTAL_CELLS as f32 * 100.0
+    }
+
+    // Iterate claimed cells, skipping empty 64-cell words
+    pub fn claimed_cells(&self) -> impl Iterator<Item = (usize, usize)> + '_ {
+        self.claimed.iter().enumerate().filter(|(_, &word)| word != 0).flat_map(|(word_index, &word)| {
+            (0..WORD_BITS)
+                .filter(move |bit| word & (1 << bit) != 0)
+                .map(move |bit| word_index * WORD_BITS + bit)
+                .filter(|&index| index < TOTAL_CELLS)
+                .map(|index| (index % GRID_SIZE, index / GRID_SIZE))
+        })
+    }
+
+    pub fn has_changes(&self) -> bool {
+        self.all_dirty || !self.dirty.is_empty()
+    }
+
+    // Cells changed since the last clear_changes(); every cell after a reset
+    pub fn changed_cells(&self) -> impl Iterator<Item = (usize, usize)> + '_ {
+        let all = self
+            .all_dirty
+            .then(|| (0..GRID_SIZE).flat_map(|y| (0..GRID_SIZE).map(move |x| (x, y))));
+        let partial = (!self.all_dirty).then(|| self.dirty.iter().copied());
+        all.into_iter().flatten().chain(partial.into_iter().flatten())
+    }
+
+    pub fn clear_changes(&mut self) {
+        self.dirty.clear();
+        self.all_dirty = false;
+    }
+}
+
+// Runs in Last so every consumer in Update/PostUpdate has seen this frame's changes
+pub fn clear_grid_changes(mut grid: ResMut<GameGrid>) {
+    if grid.has_changes() {
+        grid.clear_changes();
+    }
+}
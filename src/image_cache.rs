@@ -0,0 +1,149 @@
+use image::imageops::FilterType;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::thread;
+use std::time::UNIX_EPOCH;
+
+use crate::{WINDOW_HEIGHT, WINDOW_WIDTH};
+
+// Downscaled copies of large background images live here (relative to assets/)
+const CACHE_DIR: &str = "cache/images";
+
+// Name shared by every cached copy of a source: its path under images/ without the
+// extension, so same-named pictures in different theme folders don't clash
+fn cache_stem(asset_path: &str) -> Option<String> {
+    let path = asset_path.strip_prefix("images/").unwrap_or(asset_path);
+    let (stem, _extension) = path.rsplit_once('.')?;
+    Some(stem.replace('/', "_"))
+}
+
+// File name of the cached copy: source stem, hash of the source's size and modification
+// time, and target size. A changed source gets a new hash, so stale copies are never picked up.
+fn cache_file_name(stem: &str, source: &Path, hash: u64, width: u32, height: u32) -> Option<String> {
+    let extension = source.extension()?.to_str()?.to_lowercase();
+    Some(format!("{}-{:016x}-{}x{}.{}", stem, hash, width, height, extension))
+}
+
+// FNV-1a, stable across builds unlike std's DefaultHasher
+fn hash_bytes(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf2_9ce4_8422_2325, |hash, &byte| {
+        (hash ^ byte as u64).wrapping_mul(0x0000_0100_0000_01b3)
+    })
+}
+
+// Identifies a version of the source from its metadata alone, so looking up a cached copy
+// never reads the whole image
+fn source_version(source: &Path) -> Option<u64> {
+    let metadata = fs::metadata(source).ok()?;
+    let modified = metadata.modified().ok()?.duration_since(UNIX_EPOCH).ok()?;
+    let mut bytes = metadata.len().to_le_bytes().to_vec();
+    bytes.extend_from_slice(&modified.as_nanos().to_le_bytes());
+    Some(hash_bytes(&bytes))
+}
+
+// Smallest size that still covers the window at the image's own aspect ratio,
+// or None if the image isn't larger than that already
+fn target_size(width: u32, height: u32) -> Option<(u32, u32)> {
+    let scale = (WINDOW_WIDTH / width as f32).max(WINDOW_HEIGHT / height as f32);
+    if scale >= 1.0 {
+        return None;
+    }
+    Some(((width as f32 * scale).ceil() as u32, (height as f32 * scale).ceil() as u32))
+}
+
+struct CacheEntry {
+    source: PathBuf,
+    cached: PathBuf,
+    asset_path: String,
+    stem: String,
+    target: (u32, u32),
+}
+
+fn cache_entry(asset_path: &str) -> Option<CacheEntry> {
+    let source = Path::new("assets").join(asset_path);
+    let (width, height) = image::image_dimensions(&source).ok()?;
+    let target = target_size(width, height)?;
+    let hash = source_version(&source)?;
+    let stem = cache_stem(asset_path)?;
+    let file_name = cache_file_name(&stem, &source, hash, target.0, target.1)?;
+
+    Some(CacheEntry {
+        cached: Path::new("assets").join(CACHE_DIR).join(&file_name),
+        asset_path: format!("{}/{}", CACHE_DIR, file_name),
+        source,
+        stem,
+        target,
+    })
+}
+
+// Write the downscaled copy and delete older copies of the same source
+fn build_entry(entry: &CacheEntry) -> Result<(), String> {
+    let picture = image::open(&entry.source).map_err(|err| err.to_string())?;
+    let resized = picture.resize_exact(entry.target.0, entry.target.1, FilterType::Triangle);
+
+    let dir = entry.cached.parent().ok_or("cache path has no directory")?;
+    fs::create_dir_all(dir).map_err(|err| err.to_string())?;
+
+    // Write to a temporary name first so a half-written file is never loaded
+    let file_name = entry.cached.file_name().and_then(|name| name.to_str()).unwrap_or_default();
+    let partial = dir.join(format!(".{}.{:?}", file_name, thread::current().id()));
+    let format = image::ImageFormat::from_path(&entry.cached).map_err(|err| err.to_string())?;
+    let result = if format == image::ImageFormat::Jpeg {
+        resized.to_rgb8().save_with_format(&partial, format)
+    } else {
+        resized.save_with_format(&partial, format)
+    };
+    result.map_err(|err| err.to_string())?;
+    fs::rename(&partial, &entry.cached).map_err(|err| err.to_string())?;
+
+    // Invalidate copies made from an earlier version of this source
+    if let Ok(existing) = fs::read_dir(dir) {
+        for other in existing.flatten() {
+            let name = other.file_name().to_string_lossy().to_string();
+            if other.path() != entry.cached && name.rsplitn(3, '-').nth(2) == Some(entry.stem.as_str()) {
+                let _ = fs::remove_file(other.path());
+            }
+        }
+    }
+    Ok(())
+}
+
+// Asset path to load for a background image: the downscaled copy if it's been built,
+// otherwise the original (and the copy is built in the background for next time)
+pub fn cached_image_path(asset_path: &str) -> String {
+    let Some(entry) = cache_entry(asset_path) else {
+        return asset_path.to_string();
+    };
+    if entry.cached.exists() {
+        return entry.asset_path;
+    }
+
+    thread::spawn(move || {
+        if let Err(err) = build_entry(&entry) {
+            println!("⚠️  Could not cache {}: {}", entry.source.display(), err);
+        }
+    });
+    asset_path.to_string()
+}
+
+// Build missing downscaled copies for every image on a background thread
+pub fn warm_image_cache(asset_paths: Vec<String>) {
+    thread::spawn(move || {
+        let mut built = 0;
+        for asset_path in asset_paths {
+            let Some(entry) = cache_entry(&asset_path) else {
+                continue;
+            };
+            if entry.cached.exists() {
+                continue;
+            }
+            match build_entry(&entry) {
+                Ok(()) => built += 1,
+                Err(err) => println!("⚠️  Could not cache {}: {}", entry.source.display(), err),
+            }
+        }
+        if built > 0 {
+            println!("🗜️  Cached {} downscaled background image(s)", built);
+        }
+    });
+}
@@ -1,10 +1,61 @@
+use bevy::asset::LoadState;
+use bevy::diagnostic::{FrameTimeDiagnosticsPlugin, LogDiagnosticsPlugin};
 use bevy::prelude::*;
-use rand::Rng;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
 use std::collections::HashSet;
 use std::fs;
+use std::path::Path;
+
+mod audio_backend;
 
 mod audio_manager;
-use audio_manager::AudioManager;
+
+mod audio_plugin;
+use audio_plugin::{AudioHandle, AudioPlugin, ToggleMute};
+
+mod background;
+use background::{fit_background_sprites, BackgroundLayout, PendingFit};
+
+mod image_cache;
+use image_cache::{cached_image_path, warm_image_cache};
+
+mod credits;
+use credits::{credits_screen_text, handle_credits_screen, picture_credit, track_credit, CreditsScreen, LevelCredits};
+
+mod fog;
+use fog::FogMask;
+
+mod grid;
+use grid::{clear_grid_changes, GameGrid};
+
+mod music;
+use music::{follow_game_state, setup_now_playing_toast, update_music, update_music_intensity};
+
+mod outline;
+use outline::{update_claimed_outline, ClaimedOutline};
+
+mod packs;
+use packs::{resolve_picture_path, split_pack_path, Difficulty, ImagePack, ImagePacks};
+
+mod procedural;
+use procedural::{fallback_picture, finish_paintings, generated_picture, level_seed, paint_in_background, ArtStyle};
+
+mod reveal;
+use reveal::{animate_reveal, visible_centre, RevealAnimation};
+
+mod shuffle_bag;
+use shuffle_bag::draw_image;
+
+mod sound_bank;
+use sound_bank::{GameSound, PlaySfx};
+
+mod synth;
+
+mod settings;
+use settings::{
+    handle_settings_screen, settings_screen_text, RevealAnimationStyle, Settings, SettingsMenu,
+};
 
 const WINDOW_WIDTH: f32 = 800.0;
 const WINDOW_HEIGHT: f32 = 600.0;
@@ -12,7 +63,10 @@ const GRID_SIZE: usize = 80;
 const CELL_SIZE: f32 = WINDOW_WIDTH / GRID_SIZE as f32;
 const PLAYER_SPEED: f32 = 200.0;
 const BASE_ENEMY_SPEED: f32 = 150.0;
+const IMAGES_DIR: &str = "assets/images";
 const LEVEL_DISPLAY_TIME: f32 = 10.0; // Show completed image for 10 seconds
+const ENEMY_MIN_SPAWN_DISTANCE: f32 = 150.0; // Keep new enemies this far from the player
+const ENEMY_SPAWN_ATTEMPTS: usize = 64; // Random picks before falling back to a full scan
 
 // Calculate number of enemies based on level
 // Level 1-6: 3 enemies
@@ -37,11 +91,9 @@ struct Enemy {
     bounce_timer: f32, // For visual feedback when bouncing
 }
 
+// Single sprite covering the playfield; its texture has one pixel per grid cell
 #[derive(Component)]
-struct RevealedCell {
-    grid_x: usize,
-    grid_y: usize,
-}
+struct FogOverlay;
 
 #[derive(Component)]
 struct BackgroundSprite;
@@ -49,20 +101,96 @@ struct BackgroundSprite;
 #[derive(Component)]
 struct SpeakerButton;
 
+// Main status / menu text, rewritten every frame by update_ui
+#[derive(Component)]
+struct HudText;
+
+// Warning line at the bottom of the screen (e.g. pictures that failed to load)
+#[derive(Component)]
+struct ImageWarningText;
+
 #[derive(Component)]
 struct SpeakerIcon;
 
-// NonSend resource because audio streams cannot be sent between threads
-struct AudioResource {
-    manager: AudioManager,
-    _stream: rodio::OutputStream,
-    _stream_handle: rodio::OutputStreamHandle,
+fn world_to_grid(pos: Vec2) -> (i32, i32) {
+    (
+        ((pos.x + WINDOW_WIDTH / 2.0) / CELL_SIZE) as i32,
+        ((pos.y + WINDOW_HEIGHT / 2.0) / CELL_SIZE) as i32,
+    )
 }
 
-#[derive(Resource)]
-struct GameGrid {
-    claimed: [[bool; GRID_SIZE]; GRID_SIZE],
-    drawing_path: Vec<(i32, i32)>,
+fn grid_to_world(x: i32, y: i32) -> Vec2 {
+    Vec2::new(
+        x as f32 * CELL_SIZE - WINDOW_WIDTH / 2.0 + CELL_SIZE / 2.0,
+        y as f32 * CELL_SIZE - WINDOW_HEIGHT / 2.0 + CELL_SIZE / 2.0,
+    )
+}
+
+// Pick the centre of a free cell at least ENEMY_MIN_SPAWN_DISTANCE away from the player.
+// Cells whose neighbours are also free are required so the 1.5-cell enemy sprite
+// doesn't immediately overlap claimed territory.
+fn find_enemy_spawn_position(grid: &GameGrid, player_pos: Vec2, rng: &mut impl Rng) -> Option<Vec2> {
+    // Only cells inside the wall-bounce limits in enemy_movement - the grid runs past the
+    // top of the window, and an enemy up there would bounce in place forever
+    let columns = 1..(WINDOW_WIDTH / CELL_SIZE) as i32 - 2;
+    let rows = 1..(WINDOW_HEIGHT / CELL_SIZE) as i32 - 2;
+    let cells = || rows.clone().flat_map(|y| columns.clone().map(move |x| (x, y)));
+    
+    let is_safe = |x: i32, y: i32| {
+        let roomy = [(0, 0), (-1, 0), (1, 0), (0, -1), (0, 1)]
+            .iter()
+            .all(|&(dx, dy)| grid.is_free(x + dx, y + dy));
+        roomy && grid_to_world(x, y).distance(player_pos) >= ENEMY_MIN_SPAWN_DISTANCE
+    };
+
+    for _ in 0..ENEMY_SPAWN_ATTEMPTS {
+        let x = rng.gen_range(columns.clone());
+        let y = rng.gen_range(rows.clone());
+        if is_safe(x, y) {
+            return Some(grid_to_world(x, y));
+        }
+    }
+
+    // Territory is crowded - collect every safe cell and pick one of them
+    let candidates: Vec<(i32, i32)> = cells().filter(|&(x, y)| is_safe(x, y)).collect();
+    if !candidates.is_empty() {
+        let (x, y) = candidates[rng.gen_range(0..candidates.len())];
+        return Some(grid_to_world(x, y));
+    }
+
+    // No cell satisfies the distance rule - settle for any free cell
+    let free: Vec<(i32, i32)> = cells().filter(|&(x, y)| grid.is_free(x, y)).collect();
+    if free.is_empty() {
+        return None;
+    }
+    let (x, y) = free[rng.gen_range(0..free.len())];
+    Some(grid_to_world(x, y))
+}
+
+fn random_enemy_velocity(rng: &mut impl Rng) -> Vec2 {
+    let vx: f32 = rng.gen_range(-1.0..1.0);
+    let vy: f32 = rng.gen_range(-1.0..1.0);
+    Vec2::new(vx, vy).normalize_or(Vec2::X) * BASE_ENEMY_SPEED
+}
+
+fn spawn_enemy(commands: &mut Commands, grid: &GameGrid, player_pos: Vec2, rng: &mut impl Rng) {
+    let position = find_enemy_spawn_position(grid, player_pos, rng).unwrap_or(Vec2::ZERO);
+
+    commands.spawn((
+        SpriteBundle {
+            sprite: Sprite {
+                color: Color::srgb(1.0, 0.0, 0.0),
+                custom_size: Some(Vec2::new(CELL_SIZE * 1.5, CELL_SIZE * 1.5)),
+                ..default()
+            },
+            transform: Transform::from_xyz(position.x, position.y, 0.5),
+            ..default()
+        },
+        Enemy {
+            velocity: random_enemy_velocity(rng),
+            bounce_timer: 0.0,
+        },
+    ));
 }
 
 #[derive(Resource)]
@@ -77,6 +205,34 @@ struct GameState {
     level_timer: f32, // Time remaining for current level (in seconds)
     time_out: bool, // Flag to indicate if level failed due to timeout
     paused: bool, // Flag to indicate if game is paused
+    run: u32, // Bumped on every restart, so anything chosen once per level is chosen again
+}
+
+// Where a level's background picture comes from
+#[derive(Clone, Debug, PartialEq)]
+enum PictureSource {
+    File(String), // Asset path relative to assets/
+    Generated { style: ArtStyle, seed: u64 }, // Abstract art painted for this level
+    Fallback { seed: u64 }, // Built-in generated picture for when no file can be shown
+}
+
+impl PictureSource {
+    fn file_path(&self) -> Option<&str> {
+        match self {
+            PictureSource::File(path) => Some(path),
+            PictureSource::Generated { .. } | PictureSource::Fallback { .. } => None,
+        }
+    }
+}
+
+impl std::fmt::Display for PictureSource {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            PictureSource::File(path) => write!(f, "{}", path),
+            PictureSource::Generated { style, seed } => write!(f, "generated {} #{:x}", style.label(), seed),
+            PictureSource::Fallback { seed } => write!(f, "generated picture #{:x}", seed),
+        }
+    }
 }
 
 #[derive(Resource)]
@@ -84,7 +240,42 @@ struct BackgroundImage {
     handle: Handle<Image>,
     revealed_percentage: f32,
     threshold_reached: bool, // Track if we've hit the threshold
-    current_image_path: String, // Track which image is currently loaded
+    current_picture: PictureSource, // Track which picture is currently shown
+    layout: Option<BackgroundLayout>, // Set once the image has loaded and been fitted
+}
+
+// Seed for everything generated during one run (level 1 until the game restarts).
+// Passing --seed <n> fixes it so a run's generated pictures can be replayed.
+#[derive(Resource)]
+struct RunSeed {
+    seed: u64,
+    fixed: bool, // Given on the command line - keep it across restarts
+}
+
+impl RunSeed {
+    fn from_args() -> Self {
+        let args: Vec<String> = std::env::args().collect();
+        let given = args
+            .windows(2)
+            .find(|pair| pair[0] == "--seed")
+            .and_then(|pair| pair[1].parse().ok());
+        RunSeed {
+            seed: given.unwrap_or_else(|| rand::thread_rng().gen()),
+            fixed: given.is_some(),
+        }
+    }
+    
+    fn start_new_run(&mut self) {
+        if !self.fixed {
+            self.seed = rand::thread_rng().gen();
+        }
+    }
+}
+
+// Shown on screen while the level uses a substitute for the picture it wanted
+#[derive(Resource, Default)]
+struct ImageWarning {
+    message: Option<String>,
 }
 
 #[derive(Clone, Debug)]
@@ -99,6 +290,7 @@ struct HighScoreList {
 }
 
 impl HighScoreList {
+    #[allow(clippy::bind_instead_of_map, clippy::unwrap_or_default)]
     fn load() -> Self {
         let entries = fs::read_to_string("highscores.txt")
             .ok()
@@ -131,6 +323,7 @@ impl HighScoreList {
         let _ = fs::write("highscores.txt", content);
     }
     
+    #[allow(clippy::unnecessary_sort_by)]
     fn add_score(&mut self, name: String, score: u32) {
         self.entries.push(HighScoreEntry { name, score });
         self.entries.sort_by(|a, b| b.score.cmp(&a.score));
@@ -146,11 +339,21 @@ impl HighScoreList {
 #[derive(Resource, PartialEq)]
 enum GamePhase {
     HighScoreScreen,
+    Settings,
+    Credits,
+    Loading, // Waiting for the level's background picture before play starts
     Playing,
     NameEntry,
     ShowingNewScores,
 }
 
+#[derive(Resource, Default)]
+struct LevelLoading {
+    elapsed: f32, // Time spent on the current loading screen
+    failed_images: Vec<String>, // Pictures that failed to load this time round
+    pick_picture: bool, // Play was just started from the start screen and level 1's picture isn't chosen yet
+}
+
 #[derive(Resource)]
 struct NameEntry {
     current_name: String,
@@ -158,24 +361,22 @@ struct NameEntry {
 }
 
 fn main() {
-    // Initialize audio before starting Bevy app
-    let (_stream, stream_handle) = rodio::OutputStream::try_default().unwrap();
-    
-    // Collect all MP3 files from assets/sounds/
-    let mut sound_files = Vec::new();
-    if let Ok(entries) = fs::read_dir("assets/sounds") {
-        for entry in entries.flatten() {
-            if let Some(path_str) = entry.path().to_str() {
-                if path_str.to_lowercase().ends_with(".mp3") {
-                    sound_files.push(entry.path());
-                }
-            }
-        }
-    }
+    // --no-audio (or no sound device) plays silently
+    let no_audio = std::env::args().any(|arg| arg == "--no-audio");
+    
+    let settings = Settings::load();
+    
+    // Downscale oversized background images in the background while the game starts
+    warm_image_cache(list_image_files());
+    
+    // --frame-stats logs average frame time once per second (for profiling)
+    let frame_stats = std::env::args().any(|arg| arg == "--frame-stats");
     
-    let audio_manager = AudioManager::new(&stream_handle, sound_files);
+    let run_seed = RunSeed::from_args();
+    println!("🌱 Run seed: {} (replay with --seed {})", run_seed.seed, run_seed.seed);
     
-    App::new()
+    let mut app = App::new();
+    app
         .add_plugins(DefaultPlugins.set(WindowPlugin {
             primary_window: Some(Window {
                 title: "Strong Lines Clone".to_string(),
@@ -185,25 +386,14 @@ fn main() {
             }),
             ..default()
         }))
-        .insert_non_send_resource(AudioResource {
-            manager: audio_manager,
-            _stream,
-            _stream_handle: stream_handle,
-        })
-        .insert_resource(GameGrid {
-            claimed: {
-                let mut grid = [[false; GRID_SIZE]; GRID_SIZE];
-                // Mark the edges as claimed (safe starting zone)
-                for i in 0..GRID_SIZE {
-                    grid[0][i] = true; // Bottom edge
-                    grid[GRID_SIZE - 1][i] = true; // Top edge
-                    grid[i][0] = true; // Left edge
-                    grid[i][GRID_SIZE - 1] = true; // Right edge
-                }
-                grid
-            },
-            drawing_path: Vec::new(),
+        .insert_resource(ClearColor(Color::BLACK)) // Letterbox bars in contain fit mode
+        .add_plugins(AudioPlugin {
+            no_audio,
+            settings: settings.audio.clone(),
         })
+        .insert_resource(GameGrid::new())
+        .init_resource::<ClaimedOutline>()
+        .init_resource::<RevealAnimation>()
         .insert_resource(GameState {
             score: 0,
             lives: 3,
@@ -215,24 +405,35 @@ fn main() {
             level_timer: 120.0, // Level 1: 2 minutes (120 seconds)
             time_out: false,
             paused: false,
+            run: 0,
         })
         .insert_resource(HighScoreList::load())
+        .insert_resource(settings)
+        .insert_resource(ImagePacks::load())
+        .init_resource::<SettingsMenu>()
+        .init_resource::<LevelLoading>()
+        .init_resource::<ImageWarning>()
+        .init_resource::<CreditsScreen>()
+        .init_resource::<LevelCredits>()
+        .insert_resource(run_seed)
         .insert_resource(GamePhase::HighScoreScreen)
         .insert_resource(NameEntry {
             current_name: String::new(),
             blink_timer: 0.0,
         })
-        .add_systems(Startup, (setup_game, load_random_image, setup_speaker_button))
+        .add_systems(Startup, (setup_game, setup_background, setup_speaker_button, setup_now_playing_toast))
         .add_systems(Update, (
-            handle_high_score_screen,
+            (handle_high_score_screen, pick_first_picture, handle_level_loading).chain(),
+            handle_theme_picker,
+            handle_settings_screen,
+            handle_credits_screen,
             handle_name_entry,
             handle_pause_and_mute,
             player_movement,
             enemy_movement,
+            rescue_trapped_enemies,
             update_enemy_visuals,
             check_collisions,
-            draw_grid,
-            update_overlay_appearance,
             reveal_background,
             update_level_timer,
             check_level_completion,
@@ -242,16 +443,32 @@ fn main() {
         ))
         .add_systems(Update, (
             update_ui,
+            update_image_warning,
+            (finish_paintings, fit_background_sprites).chain(),
             handle_speaker_button,
             update_speaker_button_appearance,
+            update_music,
+            follow_game_state,
+            update_music_intensity,
         ))
-        .run();
+        .add_systems(PostUpdate, (
+            (update_overlay_appearance, animate_reveal).chain(),
+            (update_claimed_outline, draw_grid).chain(),
+        ))
+        .add_systems(Last, clear_grid_changes);
+    
+    if frame_stats {
+        app.add_plugins((FrameTimeDiagnosticsPlugin, LogDiagnosticsPlugin::default()));
+    }
+    
+    app.run();
 }
 
-fn setup_game(mut commands: Commands, game_state: Res<GameState>) {
+fn setup_game(mut commands: Commands, game_state: Res<GameState>, grid: Res<GameGrid>) {
     commands.spawn(Camera2dBundle::default());
     
     // Spawn player at the edge
+    let player_start = Vec2::new(0.0, -WINDOW_HEIGHT / 2.0 + CELL_SIZE);
     commands.spawn((
         SpriteBundle {
             sprite: Sprite {
@@ -259,7 +476,7 @@ fn setup_game(mut commands: Commands, game_state: Res<GameState>) {
                 custom_size: Some(Vec2::new(CELL_SIZE, CELL_SIZE)),
                 ..default()
             },
-            transform: Transform::from_xyz(0.0, -WINDOW_HEIGHT / 2.0 + CELL_SIZE, 1.0),
+            transform: Transform::from_translation(player_start.extend(1.0)),
             ..default()
         },
         Player { is_drawing: false },
@@ -271,34 +488,14 @@ fn setup_game(mut commands: Commands, game_state: Res<GameState>) {
     // Level 10-12: 5 enemies, etc.
     let num_enemies = calculate_enemy_count(game_state.level);
     
-    // Spawn enemies
+    // Spawn enemies on unclaimed cells away from the player
     let mut rng = rand::thread_rng();
     for _ in 0..num_enemies {
-        let x = rng.gen_range(-WINDOW_WIDTH/4.0..WINDOW_WIDTH/4.0);
-        let y = rng.gen_range(-WINDOW_HEIGHT/4.0..WINDOW_HEIGHT/4.0);
-        let vx = rng.gen_range(-1.0..1.0);
-        let vy = rng.gen_range(-1.0..1.0);
-        let velocity = Vec2::new(vx, vy).normalize() * BASE_ENEMY_SPEED;
-        
-        commands.spawn((
-            SpriteBundle {
-                sprite: Sprite {
-                    color: Color::srgb(1.0, 0.0, 0.0),
-                    custom_size: Some(Vec2::new(CELL_SIZE * 1.5, CELL_SIZE * 1.5)),
-                    ..default()
-                },
-                transform: Transform::from_xyz(x, y, 0.5),
-                ..default()
-            },
-            Enemy { 
-                velocity,
-                bounce_timer: 0.0,
-            },
-        ));
+        spawn_enemy(&mut commands, &grid, player_start, &mut rng);
     }
     
     // Spawn UI text
-    commands.spawn(
+    commands.spawn((
         TextBundle::from_section(
             "Score: 0 | Lives: 3",
             TextStyle {
@@ -313,19 +510,65 @@ fn setup_game(mut commands: Commands, game_state: Res<GameState>) {
             left: Val::Px(10.0),
             ..default()
         }),
-    );
+        HudText,
+    ));
+    
+    // Spawn the image warning line (hidden until there's something to report)
+    commands.spawn((
+        TextBundle::from_section(
+            "",
+            TextStyle {
+                font_size: 18.0,
+                color: Color::srgb(1.0, 0.85, 0.2),
+                ..default()
+            },
+        )
+        .with_style(Style {
+            position_type: PositionType::Absolute,
+            bottom: Val::Px(10.0),
+            left: Val::Px(10.0),
+            ..default()
+        }),
+        ImageWarningText,
+    ));
 }
 
-fn load_random_image(mut commands: Commands, asset_server: Res<AssetServer>) {
-    // Dynamically scan and select random image (no exclusion for first level)
-    let random_image = get_random_image_path(None);
+// Level 1's picture is picked when play starts, so the theme and packs chosen on the
+// start screen apply to it. Until then the fog hides a placeholder.
+fn setup_background(mut commands: Commands, asset_server: Res<AssetServer>, mut images: ResMut<Assets<Image>>) {
+    let picture = fallback_source();
+    let (handle, fog_mask) = spawn_level_background(&mut commands, &asset_server, &mut images, &picture);
+    commands.insert_resource(fog_mask);
     
-    println!("Loading background image: {}", random_image);
-    
-    let handle: Handle<Image> = asset_server.load(&random_image);
+    commands.insert_resource(BackgroundImage {
+        handle,
+        revealed_percentage: 0.0,
+        threshold_reached: false,
+        current_picture: picture,
+        layout: None,
+    });
+}
+
+// Start loading a background picture and spawn the sprite showing it
+fn spawn_background_sprite(
+    commands: &mut Commands,
+    asset_server: &AssetServer,
+    images: &mut Assets<Image>,
+    picture: &PictureSource,
+) -> Handle<Image> {
+    let handle: Handle<Image> = match picture {
+        // Pack pictures are extracted from their zip, and large pictures are loaded
+        // from a downscaled copy sized for the window
+        PictureSource::File(path) => asset_server.load(cached_image_path(&resolve_picture_path(path))),
+        // Generated pictures are painted on a background task
+        &PictureSource::Generated { style, seed } => {
+            paint_in_background(commands, images, move || generated_picture(style, seed))
+        }
+        &PictureSource::Fallback { seed } => paint_in_background(commands, images, move || fallback_picture(seed)),
+    };
     
     // Spawn the background image sprite (behind everything at z=-1.0)
-    // Scale to fit window size
+    // Stretched to the window until fit_background_sprites knows the image size
     commands.spawn((
         SpriteBundle {
             texture: handle.clone(),
@@ -337,36 +580,44 @@ fn load_random_image(mut commands: Commands, asset_server: Res<AssetServer>) {
             ..default()
         },
         BackgroundSprite,
+        PendingFit,
     ));
     
-    // Spawn dark overlay sprites for each grid cell (will be removed as areas are claimed)
-    // These block the background image until removed
-    for y in 0..GRID_SIZE {
-        for x in 0..GRID_SIZE {
-            let world_x = x as f32 * CELL_SIZE - WINDOW_WIDTH / 2.0 + CELL_SIZE / 2.0;
-            let world_y = y as f32 * CELL_SIZE - WINDOW_HEIGHT / 2.0 + CELL_SIZE / 2.0;
-            
-            commands.spawn((
-                SpriteBundle {
-                    sprite: Sprite {
-                        color: Color::srgb(0.0, 0.0, 0.0), // Pure black to hide image
-                        custom_size: Some(Vec2::new(CELL_SIZE, CELL_SIZE)),
-                        ..default()
-                    },
-                    transform: Transform::from_xyz(world_x, world_y, 0.0),
-                    ..default()
-                },
-                RevealedCell { grid_x: x, grid_y: y },
-            ));
-        }
-    }
+    handle
+}
+
+// Spawn the background picture and a fresh fog overlay hiding it.
+// Shared by the first level, level advancement and restarts.
+fn spawn_level_background(
+    commands: &mut Commands,
+    asset_server: &AssetServer,
+    images: &mut Assets<Image>,
+    picture: &PictureSource,
+) -> (Handle<Image>, FogMask) {
+    let handle = spawn_background_sprite(commands, asset_server, images, picture);
+    
+    // Spawn one overlay sprite stretched over the whole grid; its mask texture has
+    // one pixel per cell so the grid's bottom-left corner sits at the window's
+    let fog_mask = FogMask::new(images);
+    let grid_extent = GRID_SIZE as f32 * CELL_SIZE;
+    commands.spawn((
+        SpriteBundle {
+            texture: fog_mask.handle.clone(),
+            transform: Transform::from_xyz(
+                -WINDOW_WIDTH / 2.0 + grid_extent / 2.0,
+                -WINDOW_HEIGHT / 2.0 + grid_extent / 2.0,
+                0.0,
+            ),
+            sprite: Sprite {
+                custom_size: Some(Vec2::new(grid_extent, grid_extent)),
+                ..default()
+            },
+            ..default()
+        },
+        FogOverlay,
+    ));
     
-    commands.insert_resource(BackgroundImage {
-        handle,
-        revealed_percentage: 0.0,
-        threshold_reached: false,
-        current_image_path: random_image,
-    });
+    (handle, fog_mask)
 }
 
 fn player_movement(
@@ -376,6 +627,7 @@ fn player_movement(
     mut grid: ResMut<GameGrid>,
     game_state: Res<GameState>,
     game_phase: Res<GamePhase>,
+    mut sounds: EventWriter<PlaySfx>,
 ) {
     if game_state.game_over || game_state.level_complete_timer.is_some() || *game_phase != GamePhase::Playing || game_state.paused {
         return; // Freeze during game over, level completion display, pause, or other phases
@@ -418,54 +670,68 @@ fn player_movement(
         
         let is_on_edge = grid_x == 0 || grid_x == GRID_SIZE as i32 - 1 
             || grid_y == 0 || grid_y == GRID_SIZE as i32 - 1;
-        let is_on_claimed = grid.claimed[grid_y.max(0).min(GRID_SIZE as i32 - 1) as usize]
-            [grid_x.max(0).min(GRID_SIZE as i32 - 1) as usize];
+        let is_on_claimed = grid.is_claimed(grid_x, grid_y);
         
         if is_on_edge || is_on_claimed {
             if player.is_drawing && !grid.drawing_path.is_empty() {
                 // Complete the drawing
-                complete_area(&mut grid);
+                let claimed = complete_area(&mut grid);
                 player.is_drawing = false;
+                sounds.send(PlaySfx {
+                    sound: GameSound::AreaClaimed,
+                    size: claimed as f32 / (GRID_SIZE * GRID_SIZE) as f32,
+                });
             }
         } else {
             if !player.is_drawing {
                 player.is_drawing = true;
                 grid.drawing_path.clear();
+                sounds.send(GameSound::LineStarted.into());
             }
             grid.drawing_path.push((grid_x, grid_y));
         }
     }
 }
 
-fn complete_area(grid: &mut GameGrid) {
+// Claim the drawn line and the area it encloses, returning how many cells were claimed
+fn complete_area(grid: &mut GameGrid) -> usize {
     // Mark the drawn path as claimed
-    for &(x, y) in &grid.drawing_path {
+    let path = std::mem::take(&mut grid.drawing_path);
+    for &(x, y) in &path {
         if x >= 0 && x < GRID_SIZE as i32 && y >= 0 && y < GRID_SIZE as i32 {
-            grid.claimed[y as usize][x as usize] = true;
+            grid.set_claimed(x as usize, y as usize, true);
         }
     }
     
     // Simple flood fill to claim enclosed areas
     let mut to_fill = HashSet::new();
-    for &(x, y) in &grid.drawing_path {
+    for &(x, y) in &path {
         // Check adjacent cells
         for (dx, dy) in [(-1, 0), (1, 0), (0, -1), (0, 1)] {
             let nx = x + dx;
             let ny = y + dy;
-            if nx >= 0 && nx < GRID_SIZE as i32 && ny >= 0 && ny < GRID_SIZE as i32 {
-                if !grid.claimed[ny as usize][nx as usize] {
-                    to_fill.insert((nx, ny));
-                }
+            if !grid.is_claimed(nx, ny) {
+                to_fill.insert((nx, ny));
             }
         }
     }
     
     // Simple area fill (this is simplified - a real implementation would be more sophisticated)
-    for (x, y) in to_fill {
-        grid.claimed[y as usize][x as usize] = true;
+    for &(x, y) in &to_fill {
+        grid.set_claimed(x as usize, y as usize, true);
+    }
+    
+    // Remember where this capture happened (the level-complete wave starts there)
+    let captured: Vec<Vec2> = path
+        .iter()
+        .chain(to_fill.iter())
+        .map(|&(x, y)| Vec2::new(x as f32 + 0.5, y as f32 + 0.5))
+        .collect();
+    if !captured.is_empty() {
+        grid.last_capture = Some(captured.iter().sum::<Vec2>() / captured.len() as f32);
     }
     
-    grid.drawing_path.clear();
+    captured.len()
 }
 
 fn enemy_movement(
@@ -474,6 +740,7 @@ fn enemy_movement(
     grid: Res<GameGrid>,
     game_state: Res<GameState>,
     game_phase: Res<GamePhase>,
+    mut sounds: EventWriter<PlaySfx>,
 ) {
     // Freeze enemies during level completion display, pause, or other phases
     if game_state.level_complete_timer.is_some() || *game_phase != GamePhase::Playing || game_state.paused {
@@ -512,18 +779,16 @@ fn enemy_movement(
         }
         
         // Check if hitting claimed areas (uncovered parts)
-        if next_grid_x >= 0 && next_grid_x < GRID_SIZE as i32 && next_grid_y >= 0 && next_grid_y < GRID_SIZE as i32 {
-            if grid.claimed[next_grid_y as usize][next_grid_x as usize] {
-                // Determine which direction caused the collision
-                let current_grid_x = ((transform.translation.x + WINDOW_WIDTH / 2.0) / CELL_SIZE) as i32;
-                let current_grid_y = ((transform.translation.y + WINDOW_HEIGHT / 2.0) / CELL_SIZE) as i32;
-                
-                if next_grid_x != current_grid_x {
-                    should_bounce_x = true;
-                }
-                if next_grid_y != current_grid_y {
-                    should_bounce_y = true;
-                }
+        if grid.is_claimed(next_grid_x, next_grid_y) {
+            // Determine which direction caused the collision
+            let current_grid_x = ((transform.translation.x + WINDOW_WIDTH / 2.0) / CELL_SIZE) as i32;
+            let current_grid_y = ((transform.translation.y + WINDOW_HEIGHT / 2.0) / CELL_SIZE) as i32;
+            
+            if next_grid_x != current_grid_x {
+                should_bounce_x = true;
+            }
+            if next_grid_y != current_grid_y {
+                should_bounce_y = true;
             }
         }
         
@@ -553,6 +818,10 @@ fn enemy_movement(
             enemy.bounce_timer = 0.2; // Flash for 0.2 seconds
         }
         
+        if should_bounce_x || should_bounce_y {
+            sounds.send(GameSound::EnemyBounce.into());
+        }
+        
         // Update bounce timer
         if enemy.bounce_timer > 0.0 {
             enemy.bounce_timer -= time.delta_seconds();
@@ -563,6 +832,37 @@ fn enemy_movement(
     }
 }
 
+// Relocate any enemy that ended up inside claimed territory (e.g. enclosed by a capture)
+fn rescue_trapped_enemies(
+    player_query: Query<&Transform, With<Player>>,
+    mut enemy_query: Query<&mut Transform, (With<Enemy>, Without<Player>)>,
+    grid: Res<GameGrid>,
+    game_state: Res<GameState>,
+    game_phase: Res<GamePhase>,
+) {
+    if game_state.level_complete_timer.is_some() || *game_phase != GamePhase::Playing || game_state.paused {
+        return;
+    }
+    
+    let player_pos = player_query
+        .get_single()
+        .map(|transform| transform.translation.truncate())
+        .unwrap_or(Vec2::ZERO);
+    let mut rng = rand::thread_rng();
+    
+    for mut transform in enemy_query.iter_mut() {
+        let (grid_x, grid_y) = world_to_grid(transform.translation.truncate());
+        if !grid.is_claimed(grid_x, grid_y) {
+            continue;
+        }
+        
+        if let Some(position) = find_enemy_spawn_position(&grid, player_pos, &mut rng) {
+            transform.translation.x = position.x;
+            transform.translation.y = position.y;
+        }
+    }
+}
+
 fn check_collisions(
     player_query: Query<(&Transform, &Player)>,
     enemy_query: Query<&Transform, With<Enemy>>,
@@ -570,7 +870,7 @@ fn check_collisions(
     mut grid: ResMut<GameGrid>,
     high_score_list: Res<HighScoreList>,
     mut game_phase: ResMut<GamePhase>,
-    audio: NonSend<AudioResource>,
+    mut sounds: EventWriter<PlaySfx>,
 ) {
     if game_state.game_over {
         return;
@@ -592,6 +892,7 @@ fn check_collisions(
                     println!("💥 Enemy hit your line!");
                     game_state.lives -= 1;
                     grid.drawing_path.clear();
+                    sounds.send(GameSound::LifeLost.into());
                     
                     println!("❤️  Lives remaining: {}", game_state.lives);
                     
@@ -599,13 +900,11 @@ fn check_collisions(
                         game_state.game_over = true;
                         println!("💀 Game Over! No lives remaining.");
                         
-                        // Play game over sound effect
-                        audio.manager.play_sound_effect("assets/negative_beeps-6008.mp3");
-                        
                         // Check if this is a high score (and score is not 0)
                         if game_state.score > 0 && high_score_list.is_high_score(game_state.score) {
                             println!("🏆 This is a high score! Please enter your name.");
                             *game_phase = GamePhase::NameEntry;
+                            sounds.send(GameSound::HighScore.into());
                         }
                     }
                     return;
@@ -618,64 +917,48 @@ fn check_collisions(
 fn draw_grid(
     mut gizmos: Gizmos,
     grid: Res<GameGrid>,
+    outline: Res<ClaimedOutline>,
     bg_image: Res<BackgroundImage>,
 ) {
     // Only draw grid elements if image hasn't been revealed yet
     if !bg_image.threshold_reached {
-        // Draw thin borders around claimed areas (so players know what's safe)
-        for y in 0..GRID_SIZE {
-            for x in 0..GRID_SIZE {
-                if grid.claimed[y][x] {
-                    let world_x = x as f32 * CELL_SIZE - WINDOW_WIDTH / 2.0 + CELL_SIZE / 2.0;
-                    let world_y = y as f32 * CELL_SIZE - WINDOW_HEIGHT / 2.0 + CELL_SIZE / 2.0;
-                    
-                    // Draw just the border outline, not a filled rectangle
-                    gizmos.rect_2d(
-                        Vec2::new(world_x, world_y),
-                        0.0,
-                        Vec2::new(CELL_SIZE, CELL_SIZE),
-                        Color::srgba(0.0, 1.0, 1.0, 0.3), // Cyan outline
-                    );
-                }
-            }
+        // Draw the merged outline of claimed areas (so players know what's safe)
+        for points in &outline.loops {
+            let closed = points.iter().chain(points.first()).copied();
+            gizmos.linestrip_2d(closed, Color::srgba(0.0, 1.0, 1.0, 0.6)); // Cyan outline
         }
     }
     
-    // Always draw current drawing path (bright yellow filled)
-    for &(x, y) in &grid.drawing_path {
-        let world_x = x as f32 * CELL_SIZE - WINDOW_WIDTH / 2.0 + CELL_SIZE / 2.0;
-        let world_y = y as f32 * CELL_SIZE - WINDOW_HEIGHT / 2.0 + CELL_SIZE / 2.0;
-        
-        gizmos.rect_2d(
-            Vec2::new(world_x, world_y),
-            0.0,
-            Vec2::new(CELL_SIZE, CELL_SIZE),
+    // Always draw current drawing path as one continuous bright yellow line
+    let mut path: Vec<(i32, i32)> = grid.drawing_path.clone();
+    path.dedup(); // The path records the player's cell every frame they move
+    if path.len() > 1 {
+        gizmos.linestrip_2d(
+            path.into_iter().map(|(x, y)| grid_to_world(x, y)),
             Color::srgb(1.0, 1.0, 0.0),
         );
+    } else if let Some(&(x, y)) = path.first() {
+        gizmos.rect_2d(grid_to_world(x, y), 0.0, Vec2::new(CELL_SIZE, CELL_SIZE), Color::srgb(1.0, 1.0, 0.0));
     }
 }
 
+#[allow(clippy::too_many_arguments)]
 fn update_ui(
-    mut text_query: Query<&mut Text>,
+    mut text_query: Query<&mut Text, With<HudText>>,
     game_state: Res<GameState>,
     grid: Res<GameGrid>,
     bg_image: Res<BackgroundImage>,
     high_score_list: Res<HighScoreList>,
     game_phase: Res<GamePhase>,
     name_entry: Res<NameEntry>,
+    settings: Res<Settings>,
+    settings_menu: Res<SettingsMenu>,
+    level_loading: Res<LevelLoading>,
+    image_packs: Res<ImagePacks>,
+    credits_screen: Res<CreditsScreen>,
+    level_credits: Res<LevelCredits>,
 ) {
-    let mut claimed_count = 0;
-    let total_cells = GRID_SIZE * GRID_SIZE;
-    
-    for row in &grid.claimed {
-        for &cell in row {
-            if cell {
-                claimed_count += 1;
-            }
-        }
-    }
-    
-    let percentage = (claimed_count as f32 / total_cells as f32 * 100.0) as u32;
+    let percentage = grid.claimed_percentage() as u32;
     let top_score = high_score_list.entries.first().map(|e| e.score).unwrap_or(0);
     
     for mut text in text_query.iter_mut() {
@@ -692,10 +975,37 @@ fn update_ui(
             display.push_str("Arrow Keys (UP/DOWN/LEFT/RIGHT) or WASD: Move Player\n");
             display.push_str("P: Pause/Resume Game\n");
             display.push_str("M: Mute/Unmute Audio\n");
+            display.push_str("[ / ]: Previous/Next Music Track\n");
             display.push_str("Green Square = You\n");
             display.push_str("Red Dots = Enemies\n");
-            display.push_str("\nPress SPACE to Start Game");
+            display.push_str(&format!("\nTheme: {} (T to change)\n", settings.theme.as_deref().unwrap_or("All")));
+            if !image_packs.packs.is_empty() {
+                display.push_str("Picture packs (number key to toggle):\n");
+                for (i, pack) in image_packs.packs.iter().take(PACK_TOGGLE_KEYS.len()).enumerate() {
+                    let enabled = !settings.disabled_packs.contains(&pack.file_name);
+                    let mut line = format!("  {}. [{}] {}", i + 1, if enabled { "x" } else { " " }, pack.manifest.name);
+                    if !pack.manifest.author.is_empty() {
+                        line.push_str(&format!(" by {}", pack.manifest.author));
+                    }
+                    if !pack.manifest.licence.is_empty() {
+                        line.push_str(&format!(" ({})", pack.manifest.licence));
+                    }
+                    display.push_str(&line);
+                    display.push('\n');
+                }
+            }
+            display.push_str("Press SPACE to Start Game | O: Settings | C: Credits");
             text.sections[0].value = display;
+        } else if *game_phase == GamePhase::Settings {
+            text.sections[0].value = settings_screen_text(&settings, &settings_menu);
+        } else if *game_phase == GamePhase::Credits {
+            text.sections[0].value = credits_screen_text(&credits_screen);
+        } else if *game_phase == GamePhase::Loading {
+            let dots = ".".repeat((level_loading.elapsed * 3.0) as usize % 4);
+            text.sections[0].value = format!(
+                "Level {} - GET READY!\n\nLoading picture{}",
+                game_state.level, dots
+            );
         } else if *game_phase == GamePhase::NameEntry {
             let cursor = if (name_entry.blink_timer % 1.0) < 0.5 { "_" } else { " " };
             text.sections[0].value = format!(
@@ -721,10 +1031,18 @@ fn update_ui(
             );
         } else if let Some(timer) = game_state.level_complete_timer {
             // Showing completed full image
-            text.sections[0].value = format!(
-                "🎊 LEVEL {} COMPLETE! 🎊 | Score: {} | Top: {} | Next Level in {:.1}s",
+            let mut display = format!(
+                "🎊 LEVEL {} COMPLETE! 🎊 | Score: {} | Top: {} | Next Level in {:.1}s (SPACE to skip)",
                 game_state.level, game_state.score, top_score, timer
             );
+            // Credit the picture being shown and the music playing
+            if let Some(credit) = &level_credits.picture {
+                display.push_str(&format!("\nPicture: {}", credit.summary()));
+            }
+            if let Some(credit) = &level_credits.track {
+                display.push_str(&format!("\nMusic: {}", credit.summary()));
+            }
+            text.sections[0].value = display;
         } else if !bg_image.threshold_reached {
             let minutes = (game_state.level_timer / 60.0) as u32;
             let seconds = (game_state.level_timer % 60.0) as u32;
@@ -749,23 +1067,14 @@ fn update_ui(
 fn reveal_background(
     mut commands: Commands,
     grid: Res<GameGrid>,
-    overlay_query: Query<(Entity, &RevealedCell)>,
+    overlay_query: Query<Entity, With<FogOverlay>>,
     mut bg_image: ResMut<BackgroundImage>,
     game_state: Res<GameState>,
+    settings: Res<Settings>,
+    mut reveal_animation: ResMut<RevealAnimation>,
 ) {
-    // Calculate claimed percentage
-    let mut claimed_count = 0;
-    let total_cells = GRID_SIZE * GRID_SIZE;
-    
-    for row in &grid.claimed {
-        for &cell in row {
-            if cell {
-                claimed_count += 1;
-            }
-        }
-    }
-    
-    let percentage = (claimed_count as f32 / total_cells as f32) * 100.0;
+    // Claimed percentage is kept up to date by GameGrid itself
+    let percentage = grid.claimed_percentage();
     bg_image.revealed_percentage = percentage;
     
     // Check if threshold is reached
@@ -773,13 +1082,17 @@ fn reveal_background(
         bg_image.threshold_reached = true;
         println!("🎉 Threshold reached at {}%! Revealing ENTIRE image!", percentage);
         
-        // Remove ALL overlay sprites to show the complete image
-        let mut removed_count = 0;
-        for (entity, _cell) in overlay_query.iter() {
-            commands.entity(entity).despawn();
-            removed_count += 1;
+        if settings.reveal_animation == RevealAnimationStyle::Instant {
+            // Remove the fog overlay to show the complete image
+            for entity in overlay_query.iter() {
+                commands.entity(entity).despawn();
+            }
+            println!("✅ Removed fog overlay - image should now be fully visible!");
+        } else {
+            // animate_reveal fades the overlay out and despawns it when done
+            let origin = grid.last_capture.unwrap_or_else(visible_centre);
+            reveal_animation.start(settings.reveal_animation, origin);
         }
-        println!("✅ Removed {} overlay sprites - image should now be fully visible!", removed_count);
     }
 }
 
@@ -802,7 +1115,7 @@ fn update_level_timer(
     time: Res<Time>,
     mut game_phase: ResMut<GamePhase>,
     high_score_list: Res<HighScoreList>,
-    audio: NonSend<AudioResource>,
+    mut sounds: EventWriter<PlaySfx>,
 ) {
     // Don't update timer if game is over, showing level completion, paused, or not playing
     if game_state.game_over || game_state.level_complete_timer.is_some() || *game_phase != GamePhase::Playing || game_state.paused {
@@ -819,21 +1132,28 @@ fn update_level_timer(
         game_state.time_out = true;
         println!("⏰ TIME OUT! Level {} failed - time expired!", game_state.level);
         
-        // Play game over sound effect
-        audio.manager.play_sound_effect("assets/negative_beeps-6008.mp3");
+        // Running out of time costs the last chance just like losing a life
+        sounds.send(GameSound::LifeLost.into());
         
         // Check if this is a high score (and score is not 0)
         if game_state.score > 0 && high_score_list.is_high_score(game_state.score) {
             println!("🏆 This is a high score! Please enter your name.");
             *game_phase = GamePhase::NameEntry;
+            sounds.send(GameSound::HighScore.into());
         }
     }
 }
 
+#[allow(clippy::too_many_arguments)]
 fn check_level_completion(
     mut game_state: ResMut<GameState>,
     bg_image: Res<BackgroundImage>,
     time: Res<Time>,
+    keyboard: Res<ButtonInput<KeyCode>>,
+    mut level_credits: ResMut<LevelCredits>,
+    image_packs: Res<ImagePacks>,
+    audio: Res<AudioHandle>,
+    mut sounds: EventWriter<PlaySfx>,
 ) {
     // Level is complete when threshold is reached (show whole image)
     if bg_image.threshold_reached && game_state.level_complete_timer.is_none() {
@@ -850,12 +1170,23 @@ fn check_level_completion(
         
         println!("👁️  Player and enemies will be hidden so you can see the image clearly!");
         game_state.level_complete_timer = Some(LEVEL_DISPLAY_TIME);
+        sounds.send(GameSound::LevelComplete.into());
+        
+        // Look up who to credit while the finished picture is on screen
+        level_credits.picture = Some(picture_credit(&bg_image.current_picture, &image_packs.packs));
+        level_credits.track = audio.current_track().as_deref().map(track_credit);
     }
     
     // Count down timer
     if let Some(timer) = game_state.level_complete_timer.as_mut() {
         *timer -= time.delta_seconds();
         
+        // SPACE or ENTER skips the rest of the display time
+        if keyboard.just_pressed(KeyCode::Space) || keyboard.just_pressed(KeyCode::Enter) {
+            println!("⏩ Skipping remaining image display time");
+            *timer = 0.0;
+        }
+        
         // Debug: Print remaining time every second
         if (*timer * 10.0) as i32 % 10 == 0 && *timer > 0.1 {
             println!("⏱️  Image display time remaining: {:.1}s", timer);
@@ -871,32 +1202,39 @@ fn check_level_completion(
 }
 
 fn update_overlay_appearance(
-    mut overlay_query: Query<(&mut Sprite, &RevealedCell)>,
     grid: Res<GameGrid>,
     bg_image: Res<BackgroundImage>,
+    mut fog_mask: ResMut<FogMask>,
+    mut images: ResMut<Assets<Image>>,
+    settings: Res<Settings>,
 ) {
-    // Update overlay sprites to show claimed vs unclaimed areas before threshold
-    if !bg_image.threshold_reached {
-        for (mut sprite, cell) in overlay_query.iter_mut() {
-            if grid.claimed[cell.grid_y][cell.grid_x] {
-                // Claimed but not revealed yet - darker gray
-                sprite.color = Color::srgb(0.15, 0.15, 0.15);
-            } else {
-                // Not claimed - pure black
-                sprite.color = Color::srgb(0.0, 0.0, 0.0);
-            }
-        }
+    // Update mask pixels to show claimed vs unclaimed areas before threshold.
+    // Runs in PostUpdate so it sees every cell changed during Update.
+    if bg_image.threshold_reached {
+        return;
     }
+    
+    fog_mask.sync(&grid, &mut images, settings.reveal_mode);
 }
 
+#[allow(clippy::too_many_arguments)]
 fn advance_level(
     mut game_state: ResMut<GameState>,
     mut commands: Commands,
     asset_server: Res<AssetServer>,
-    overlay_query: Query<Entity, With<RevealedCell>>,
+    overlay_query: Query<Entity, With<FogOverlay>>,
+    mut images: ResMut<Assets<Image>>,
+    mut fog_mask: ResMut<FogMask>,
+    mut reveal_animation: ResMut<RevealAnimation>,
     bg_sprite_query: Query<Entity, With<BackgroundSprite>>,
     mut grid: ResMut<GameGrid>,
     mut bg_image: ResMut<BackgroundImage>,
+    player_query: Query<&Transform, With<Player>>,
+    mut enemy_query: Query<&mut Transform, (With<Enemy>, Without<Player>)>,
+    mut game_phase: ResMut<GamePhase>,
+    settings: Res<Settings>,
+    image_packs: Res<ImagePacks>,
+    run_seed: Res<RunSeed>,
 ) {
     // Only advance when ready_to_advance flag is set
     if !game_state.ready_to_advance {
@@ -927,42 +1265,39 @@ fn advance_level(
         game_state.level_timer % 60.0
     );
     
+    // Reset grid (keep edges claimed) before placing enemies on it
+    grid.reset();
+    
+    let player_pos = player_query
+        .get_single()
+        .map(|transform| transform.translation.truncate())
+        .unwrap_or(Vec2::ZERO);
+    let mut rng = rand::thread_rng();
+    
+    // Move surviving enemies to fresh positions so none start next to the player
+    for mut transform in enemy_query.iter_mut() {
+        if let Some(position) = find_enemy_spawn_position(&grid, player_pos, &mut rng) {
+            transform.translation.x = position.x;
+            transform.translation.y = position.y;
+        }
+    }
+    
     // Spawn additional enemies if needed for this level
     if new_enemy_count > old_enemy_count {
         let additional_enemies = new_enemy_count - old_enemy_count;
         println!("🔴 Adding {} more enemy/enemies! Total enemies: {}", additional_enemies, new_enemy_count);
         
-        let mut rng = rand::thread_rng();
         for _ in 0..additional_enemies {
-            let x = rng.gen_range(-WINDOW_WIDTH/4.0..WINDOW_WIDTH/4.0);
-            let y = rng.gen_range(-WINDOW_HEIGHT/4.0..WINDOW_HEIGHT/4.0);
-            let vx = rng.gen_range(-1.0..1.0);
-            let vy = rng.gen_range(-1.0..1.0);
-            let velocity = Vec2::new(vx, vy).normalize() * BASE_ENEMY_SPEED;
-            
-            commands.spawn((
-                SpriteBundle {
-                    sprite: Sprite {
-                        color: Color::srgb(1.0, 0.0, 0.0),
-                        custom_size: Some(Vec2::new(CELL_SIZE * 1.5, CELL_SIZE * 1.5)),
-                        ..default()
-                    },
-                    transform: Transform::from_xyz(x, y, 0.5),
-                    ..default()
-                },
-                Enemy { 
-                    velocity,
-                    bounce_timer: 0.0,
-                },
-            ));
+            spawn_enemy(&mut commands, &grid, player_pos, &mut rng);
         }
     }
     
     // Reset background image state
     bg_image.revealed_percentage = 0.0;
     bg_image.threshold_reached = false;
+    reveal_animation.stop();
     
-    // Despawn all existing overlay sprites
+    // Despawn the existing fog overlay
     for entity in overlay_query.iter() {
         commands.entity(entity).despawn();
     }
@@ -972,68 +1307,24 @@ fn advance_level(
         commands.entity(entity).despawn();
     }
     
-    // Reset grid (keep edges claimed)
-    grid.claimed = {
-        let mut new_grid = [[false; GRID_SIZE]; GRID_SIZE];
-        // Mark the edges as claimed (safe starting zone)
-        for i in 0..GRID_SIZE {
-            new_grid[0][i] = true; // Bottom edge
-            new_grid[GRID_SIZE - 1][i] = true; // Top edge
-            new_grid[i][0] = true; // Left edge
-            new_grid[i][GRID_SIZE - 1] = true; // Right edge
-        }
-        new_grid
-    };
-    grid.drawing_path.clear();
-    
     // Get previous image path to exclude it
-    let previous_image = bg_image.current_image_path.clone();
+    let previous_image: Vec<String> = bg_image.current_picture.file_path().map(String::from).into_iter().collect();
     
     // Dynamically scan and load new random image (excluding previous one)
-    let random_image = get_random_image_path(Some(&previous_image));
+    let picture = choose_picture(&previous_image, &settings, &image_packs.packs, game_state.level, run_seed.seed);
     
-    println!("Loading new image for level {}: {}", game_state.level, random_image);
+    println!("Loading new image for level {}: {}", game_state.level, picture);
     
-    let handle: Handle<Image> = asset_server.load(&random_image);
+    let (handle, new_fog_mask) = spawn_level_background(&mut commands, &asset_server, &mut images, &picture);
+    *fog_mask = new_fog_mask;
     
     // Update background image resource
-    bg_image.handle = handle.clone();
-    bg_image.current_image_path = random_image;
-    commands.spawn((
-        SpriteBundle {
-            texture: handle.clone(),
-            transform: Transform::from_xyz(0.0, 0.0, -1.0),
-            sprite: Sprite {
-                custom_size: Some(Vec2::new(WINDOW_WIDTH, WINDOW_HEIGHT)),
-                ..default()
-            },
-            ..default()
-        },
-        BackgroundSprite,
-    ));
-    
-    // Spawn new dark overlay sprites
-    for y in 0..GRID_SIZE {
-        for x in 0..GRID_SIZE {
-            let world_x = x as f32 * CELL_SIZE - WINDOW_WIDTH / 2.0 + CELL_SIZE / 2.0;
-            let world_y = y as f32 * CELL_SIZE - WINDOW_HEIGHT / 2.0 + CELL_SIZE / 2.0;
-            
-            commands.spawn((
-                SpriteBundle {
-                    sprite: Sprite {
-                        color: Color::srgb(0.0, 0.0, 0.0),
-                        custom_size: Some(Vec2::new(CELL_SIZE, CELL_SIZE)),
-                        ..default()
-                    },
-                    transform: Transform::from_xyz(world_x, world_y, 0.0),
-                    ..default()
-                },
-                RevealedCell { grid_x: x, grid_y: y },
-            ));
-        }
-    }
-    
     bg_image.handle = handle;
+    bg_image.current_picture = picture;
+    bg_image.layout = None;
+    
+    // Hold play until the new picture has loaded
+    *game_phase = GamePhase::Loading;
 }
 
 fn hide_entities_during_completion(
@@ -1063,19 +1354,24 @@ fn hide_entities_during_completion(
     }
 }
 
+#[allow(clippy::too_many_arguments)]
 fn restart_game(
     keyboard: Res<ButtonInput<KeyCode>>,
     mut game_state: ResMut<GameState>,
     mut grid: ResMut<GameGrid>,
     mut commands: Commands,
     asset_server: Res<AssetServer>,
-    overlay_query: Query<Entity, With<RevealedCell>>,
+    overlay_query: Query<Entity, With<FogOverlay>>,
+    mut images: ResMut<Assets<Image>>,
+    mut fog_mask: ResMut<FogMask>,
+    mut reveal_animation: ResMut<RevealAnimation>,
     bg_sprite_query: Query<Entity, With<BackgroundSprite>>,
     mut bg_image: ResMut<BackgroundImage>,
     mut player_query: Query<&mut Transform, With<Player>>,
     enemy_query: Query<Entity, With<Enemy>>,
     mut game_phase: ResMut<GamePhase>,
     mut name_entry: ResMut<NameEntry>,
+    (settings, image_packs, mut run_seed): (Res<Settings>, Res<ImagePacks>, ResMut<RunSeed>), // Grouped to stay within Bevy's parameter limit
 ) {
     // Only restart when game is over and R key is pressed
     // OR when showing new scores and R key is pressed
@@ -1099,30 +1395,22 @@ fn restart_game(
     game_state.ready_to_advance = false;
     game_state.level_timer = 120.0; // Reset to 2 minutes for level 1
     game_state.paused = false; // Reset pause state
+    game_state.run += 1;
     
-    // Reset game phase
-    *game_phase = GamePhase::Playing;
+    // Reset game phase (play starts once the new picture has loaded)
+    *game_phase = GamePhase::Loading;
     
     // Reset name entry
     name_entry.current_name.clear();
     name_entry.blink_timer = 0.0;
     
     // Reset grid (keep edges claimed)
-    grid.claimed = {
-        let mut new_grid = [[false; GRID_SIZE]; GRID_SIZE];
-        for i in 0..GRID_SIZE {
-            new_grid[0][i] = true;
-            new_grid[GRID_SIZE - 1][i] = true;
-            new_grid[i][0] = true;
-            new_grid[i][GRID_SIZE - 1] = true;
-        }
-        new_grid
-    };
-    grid.drawing_path.clear();
+    grid.reset();
     
     // Reset player position to bottom edge
+    let player_start = Vec2::new(0.0, -WINDOW_HEIGHT / 2.0 + CELL_SIZE);
     if let Ok(mut transform) = player_query.get_single_mut() {
-        transform.translation = Vec3::new(0.0, -WINDOW_HEIGHT / 2.0 + CELL_SIZE, 1.0);
+        transform.translation = player_start.extend(1.0);
     }
     
     // Despawn all existing enemies
@@ -1134,30 +1422,11 @@ fn restart_game(
     let num_enemies = calculate_enemy_count(1);
     let mut rng = rand::thread_rng();
     for _ in 0..num_enemies {
-        let x = rng.gen_range(-WINDOW_WIDTH/4.0..WINDOW_WIDTH/4.0);
-        let y = rng.gen_range(-WINDOW_HEIGHT/4.0..WINDOW_HEIGHT/4.0);
-        let vx = rng.gen_range(-1.0..1.0);
-        let vy = rng.gen_range(-1.0..1.0);
-        let velocity = Vec2::new(vx, vy).normalize() * BASE_ENEMY_SPEED;
-        
-        commands.spawn((
-            SpriteBundle {
-                sprite: Sprite {
-                    color: Color::srgb(1.0, 0.0, 0.0),
-                    custom_size: Some(Vec2::new(CELL_SIZE * 1.5, CELL_SIZE * 1.5)),
-                    ..default()
-                },
-                transform: Transform::from_xyz(x, y, 0.5),
-                ..default()
-            },
-            Enemy { 
-                velocity,
-                bounce_timer: 0.0,
-            },
-        ));
+        spawn_enemy(&mut commands, &grid, player_start, &mut rng);
     }
     
-    // Despawn all existing overlay sprites
+    // Despawn the existing fog overlay (cutting short any reveal animation)
+    reveal_animation.stop();
     for entity in overlay_query.iter() {
         commands.entity(entity).despawn();
     }
@@ -1167,126 +1436,201 @@ fn restart_game(
         commands.entity(entity).despawn();
     }
     
-    // Load new random image
-    let random_image = get_random_image_path(None);
-    println!("Loading new image for restart: {}", random_image);
-    
-    let handle: Handle<Image> = asset_server.load(&random_image);
-    
-    // Spawn new background sprite
-    commands.spawn((
-        SpriteBundle {
-            texture: handle.clone(),
-            transform: Transform::from_xyz(0.0, 0.0, -1.0),
-            sprite: Sprite {
-                custom_size: Some(Vec2::new(WINDOW_WIDTH, WINDOW_HEIGHT)),
-                ..default()
-            },
-            ..default()
-        },
-        BackgroundSprite,
-    ));
+    // Load new random image (a new run gets new generated pictures)
+    run_seed.start_new_run();
+    let picture = choose_picture(&[], &settings, &image_packs.packs, 1, run_seed.seed);
+    println!("Loading new image for restart: {}", picture);
     
-    // Spawn new overlay sprites
-    for y in 0..GRID_SIZE {
-        for x in 0..GRID_SIZE {
-            let world_x = x as f32 * CELL_SIZE - WINDOW_WIDTH / 2.0 + CELL_SIZE / 2.0;
-            let world_y = y as f32 * CELL_SIZE - WINDOW_HEIGHT / 2.0 + CELL_SIZE / 2.0;
-            
-            commands.spawn((
-                SpriteBundle {
-                    sprite: Sprite {
-                        color: Color::srgb(0.0, 0.0, 0.0),
-                        custom_size: Some(Vec2::new(CELL_SIZE, CELL_SIZE)),
-                        ..default()
-                    },
-                    transform: Transform::from_xyz(world_x, world_y, 0.0),
-                    ..default()
-                },
-                RevealedCell { grid_x: x, grid_y: y },
-            ));
-        }
-    }
+    let (handle, new_fog_mask) = spawn_level_background(&mut commands, &asset_server, &mut images, &picture);
+    *fog_mask = new_fog_mask;
     
     // Update background image resource
     bg_image.handle = handle;
-    bg_image.current_image_path = random_image;
+    bg_image.current_picture = picture;
+    bg_image.layout = None;
     bg_image.revealed_percentage = 0.0;
     bg_image.threshold_reached = false;
     
     println!("✅ Game restarted!");
 }
 
-fn get_random_image_path(exclude_path: Option<&str>) -> String {
-    let images_dir = "assets/images";
+// Asset paths (relative to assets/) of every PNG or JPEG in assets/images and its subfolders
+fn list_image_files() -> Vec<String> {
     let mut image_files = Vec::new();
-    
+    collect_image_files(Path::new(IMAGES_DIR), &mut image_files);
+    image_files.sort();
+    image_files
+}
+
+fn collect_image_files(dir: &Path, image_files: &mut Vec<String>) {
     // Try to read the directory
-    if let Ok(entries) = fs::read_dir(images_dir) {
-        for entry in entries.flatten() {
-            if let Ok(file_type) = entry.file_type() {
-                if file_type.is_file() {
-                    if let Some(path_str) = entry.path().to_str() {
-                        let path_lower = path_str.to_lowercase();
-                        // Check for valid image extensions
-                        if path_lower.ends_with(".png") 
-                            || path_lower.ends_with(".jpg") 
-                            || path_lower.ends_with(".jpeg") {
-                            // Convert to asset path format (relative to assets folder)
-                            if let Some(rel_path) = path_str.strip_prefix("assets/") {
-                                image_files.push(rel_path.to_string());
-                            }
-                        }
-                    }
-                }
+    let Ok(entries) = fs::read_dir(dir) else {
+        return;
+    };
+    for entry in entries.flatten() {
+        let path = entry.path();
+        let Ok(file_type) = entry.file_type() else {
+            continue;
+        };
+        // Skip hidden files and folders (.DS_Store, .git, ...)
+        if entry.file_name().to_string_lossy().starts_with('.') {
+            continue;
+        }
+        if file_type.is_dir() {
+            collect_image_files(&path, image_files);
+            continue;
+        }
+        
+        // Check for valid image extensions
+        let path_lower = path.to_string_lossy().to_lowercase();
+        if path_lower.ends_with(".png") || path_lower.ends_with(".jpg") || path_lower.ends_with(".jpeg") {
+            // Convert to asset path format (relative to assets folder, forward slashes on every OS)
+            if let Ok(rel_path) = path.strip_prefix("assets") {
+                let components: Vec<String> = rel_path
+                    .components()
+                    .map(|component| component.as_os_str().to_string_lossy().to_string())
+                    .collect();
+                image_files.push(components.join("/"));
             }
         }
     }
+}
+
+// Every picture the game can pick from: assets/images plus the pictures in enabled packs
+fn list_picture_files(settings: &Settings, packs: &[ImagePack]) -> Vec<String> {
+    let mut picture_files = list_image_files();
+    for pack in packs {
+        if !settings.disabled_packs.contains(&pack.file_name) {
+            picture_files.extend(pack.image_paths());
+        }
+    }
+    picture_files
+}
+
+// Theme of a picture: the first subfolder of assets/images (or of its pack) it sits in, if any
+fn image_theme(picture_path: &str) -> Option<&str> {
+    let inner = match split_pack_path(picture_path) {
+        Some((_, entry)) => entry,
+        None => picture_path.strip_prefix("images/")?,
+    };
+    let mut parts = inner.split('/');
+    let first = parts.next()?;
+    parts.next().map(|_| first)
+}
+
+// Names of the theme subfolders that contain pictures
+fn list_themes(settings: &Settings, packs: &[ImagePack]) -> Vec<String> {
+    let mut themes: Vec<String> = list_picture_files(settings, packs)
+        .iter()
+        .filter_map(|path| image_theme(path).map(String::from))
+        .collect();
+    themes.sort();
+    themes.dedup();
+    themes
+}
+
+// Pack pictures marked as hard are held back until this level
+const HARD_PICTURES_FROM_LEVEL: u32 = 4;
+
+// Next picture for a level from assets/images and enabled packs (limited to the level's theme)
+// in shuffle-bag order, avoiding the excluded paths where possible. None if there are no pictures.
+fn get_random_image_path(
+    exclude_paths: &[String],
+    settings: &Settings,
+    packs: &[ImagePack],
+    level: u32,
+) -> Option<String> {
+    let mut image_files = list_picture_files(settings, packs);
+    let theme = settings.theme_for_level(level);
     
-    // If no images found, return a fallback
     if image_files.is_empty() {
-        println!("⚠️  No images found in {}! Please add PNG or JPEG files.", images_dir);
-        return "images/placeholder.png".to_string();
+        println!("⚠️  No images found in {}! Please add PNG or JPEG files.", IMAGES_DIR);
+        return None;
     }
     
-    // Filter out the excluded path if provided
-    if let Some(exclude) = exclude_path {
-        image_files.retain(|path| path != exclude);
-        
-        // If we filtered out all images (only 1 image available), add them back
-        if image_files.is_empty() {
-            println!("⚠️  Only one image available - cannot avoid repetition");
-            if let Ok(entries) = fs::read_dir(images_dir) {
-                for entry in entries.flatten() {
-                    if let Ok(file_type) = entry.file_type() {
-                        if file_type.is_file() {
-                            if let Some(path_str) = entry.path().to_str() {
-                                let path_lower = path_str.to_lowercase();
-                                if path_lower.ends_with(".png") 
-                                    || path_lower.ends_with(".jpg") 
-                                    || path_lower.ends_with(".jpeg") {
-                                    if let Some(rel_path) = path_str.strip_prefix("assets/") {
-                                        image_files.push(rel_path.to_string());
-                                    }
-                                }
-                            }
-                        }
-                    }
+    let mut theme = theme;
+    if let Some(name) = theme {
+        let themed: Vec<String> = image_files
+            .iter()
+            .filter(|path| image_theme(path) == Some(name))
+            .cloned()
+            .collect();
+        if themed.is_empty() {
+            println!("⚠️  No images in theme '{}' - using all images", name);
+            theme = None;
+        } else {
+            image_files = themed;
+        }
+    }
+    
+    if !exclude_paths.is_empty() {
+        println!("🚫 Excluding: {}", exclude_paths.join(", "));
+    }
+    let mut exclude_paths = exclude_paths.to_vec();
+    
+    // Keep pictures their pack calls hard for later levels
+    if level < HARD_PICTURES_FROM_LEVEL {
+        for pack in packs {
+            for info in &pack.manifest.images {
+                if info.difficulty == Some(Difficulty::Hard) {
+                    exclude_paths.push(pack.picture_path(&info.file));
                 }
             }
-        } else {
-            println!("🚫 Excluding previous image: {}", exclude);
         }
     }
     
-    // Select random image
+    // Every image is shown once before any repeats, even across restarts
     let mut rng = rand::thread_rng();
-    let selected = &image_files[rng.gen_range(0..image_files.len())];
+    let selected = draw_image(theme, &image_files, &exclude_paths, &mut rng)?;
+    
+    println!("📁 Found {} images{}", image_files.len(), theme.map(|name| format!(" in theme '{}'", name)).unwrap_or_default());
+    println!("🎲 Next from the shuffle bag: {}", selected);
+    
+    // Pack pictures may come with a title and credit
+    if let Some((zip_name, entry)) = split_pack_path(&selected) {
+        let pack = packs.iter().find(|pack| pack.file_name == zip_name);
+        if let Some(info) = pack.and_then(|pack| pack.image_info(entry)) {
+            println!(
+                "🖼️  {} - {}",
+                info.title.as_deref().unwrap_or(entry),
+                info.credit.as_deref().unwrap_or("no credit given")
+            );
+        }
+    }
     
-    println!("📁 Found {} images in directory", image_files.len());
-    println!("🎲 Randomly selected: {}", selected);
+    Some(selected)
+}
+
+// Picture for a level: a random image file from the level's theme or generated art, chosen
+// by the configured weights. Generated art is also used whenever the folder has no images.
+fn choose_picture(
+    exclude_paths: &[String],
+    settings: &Settings,
+    packs: &[ImagePack],
+    level: u32,
+    run_seed: u64,
+) -> PictureSource {
+    let seed = level_seed(run_seed, level);
+    let mut rng = StdRng::seed_from_u64(seed);
+    let weights = &settings.pictures;
+    let total = weights.folder.max(0.0) + weights.generated.max(0.0);
+    let generated = total > 0.0 && rng.gen_range(0.0..total) < weights.generated.max(0.0);
+    
+    if !generated {
+        if let Some(path) = get_random_image_path(exclude_paths, settings, packs, level) {
+            return PictureSource::File(path);
+        }
+    }
     
-    selected.clone()
+    let style = ArtStyle::ALL[rng.gen_range(0..ArtStyle::ALL.len())];
+    println!("🎨 Painting a {} picture", style.label());
+    PictureSource::Generated { style, seed }
+}
+
+fn fallback_source() -> PictureSource {
+    PictureSource::Fallback {
+        seed: rand::thread_rng().gen(),
+    }
 }
 
 fn setup_speaker_button(mut commands: Commands, asset_server: Res<AssetServer>) {
@@ -1334,11 +1678,11 @@ fn handle_speaker_button(
         &Interaction,
         (Changed<Interaction>, With<SpeakerButton>),
     >,
-    mut audio_resource: NonSendMut<AudioResource>,
+    mut mute_events: EventWriter<ToggleMute>,
 ) {
     for interaction in interaction_query.iter() {
         if *interaction == Interaction::Pressed {
-            audio_resource.manager.toggle_mute();
+            mute_events.send(ToggleMute);
         }
     }
 }
@@ -1346,10 +1690,10 @@ fn handle_speaker_button(
 fn update_speaker_button_appearance(
     mut button_query: Query<(&mut BackgroundColor, &mut BorderColor), With<SpeakerButton>>,
     mut icon_query: Query<&mut UiImage, With<SpeakerIcon>>,
-    audio_resource: NonSend<AudioResource>,
+    settings: Res<Settings>,
 ) {
     // Update button appearance based on mute state
-    let is_muted = audio_resource.manager.is_muted();
+    let is_muted = settings.audio.muted;
     
     for (mut bg_color, mut border_color) in button_query.iter_mut() {
         if is_muted {
@@ -1375,6 +1719,11 @@ fn update_speaker_button_appearance(
 fn handle_high_score_screen(
     keyboard: Res<ButtonInput<KeyCode>>,
     mut game_phase: ResMut<GamePhase>,
+    mut loading: ResMut<LevelLoading>,
+    settings: Res<Settings>,
+    image_packs: Res<ImagePacks>,
+    mut credits_screen: ResMut<CreditsScreen>,
+    audio: Res<AudioHandle>,
 ) {
     if *game_phase != GamePhase::HighScoreScreen {
         return;
@@ -1382,7 +1731,196 @@ fn handle_high_score_screen(
     
     if keyboard.just_pressed(KeyCode::Space) {
         println!("🎮 Starting game...");
-        *game_phase = GamePhase::Playing;
+        loading.pick_picture = true;
+        *game_phase = GamePhase::Loading;
+    } else if keyboard.just_pressed(KeyCode::KeyO) {
+        *game_phase = GamePhase::Settings;
+    } else if keyboard.just_pressed(KeyCode::KeyC) {
+        credits_screen.open(&list_picture_files(&settings, &image_packs.packs), &image_packs.packs, &audio.tracks());
+        *game_phase = GamePhase::Credits;
+    }
+}
+
+// Keys that toggle the first nine picture packs on the start screen
+const PACK_TOGGLE_KEYS: [KeyCode; 9] = [
+    KeyCode::Digit1,
+    KeyCode::Digit2,
+    KeyCode::Digit3,
+    KeyCode::Digit4,
+    KeyCode::Digit5,
+    KeyCode::Digit6,
+    KeyCode::Digit7,
+    KeyCode::Digit8,
+    KeyCode::Digit9,
+];
+
+fn handle_theme_picker(
+    keyboard: Res<ButtonInput<KeyCode>>,
+    game_phase: Res<GamePhase>,
+    mut settings: ResMut<Settings>,
+    image_packs: Res<ImagePacks>,
+    mut sounds: EventWriter<PlaySfx>,
+) {
+    if *game_phase != GamePhase::HighScoreScreen {
+        return;
+    }
+    
+    if keyboard.just_pressed(KeyCode::KeyT) {
+        // Cycle All -> each theme folder -> All
+        let themes = list_themes(&settings, &image_packs.packs);
+        let next = match &settings.theme {
+            None => themes.first().cloned(),
+            Some(current) => themes
+                .iter()
+                .position(|theme| theme == current)
+                .and_then(|i| themes.get(i + 1).cloned()),
+        };
+        println!("🖼️  Theme: {}", next.as_deref().unwrap_or("All"));
+        settings.theme = next;
+        settings.save();
+        sounds.send(GameSound::MenuMove.into());
+    }
+    
+    // Enable or disable picture packs
+    for (pack, key) in image_packs.packs.iter().zip(PACK_TOGGLE_KEYS) {
+        if !keyboard.just_pressed(key) {
+            continue;
+        }
+        if let Some(i) = settings.disabled_packs.iter().position(|name| *name == pack.file_name) {
+            settings.disabled_packs.remove(i);
+            println!("📦 Enabled pack: {}", pack.manifest.name);
+        } else {
+            settings.disabled_packs.push(pack.file_name.clone());
+            println!("📦 Disabled pack: {}", pack.manifest.name);
+        }
+        settings.save();
+    }
+}
+
+// Pick level 1's picture once play starts, now that the theme is settled
+#[allow(clippy::too_many_arguments)]
+fn pick_first_picture(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    mut images: ResMut<Assets<Image>>,
+    mut bg_image: ResMut<BackgroundImage>,
+    mut fog_mask: ResMut<FogMask>,
+    mut loading: ResMut<LevelLoading>,
+    overlay_query: Query<Entity, With<FogOverlay>>,
+    bg_sprite_query: Query<Entity, With<BackgroundSprite>>,
+    settings: Res<Settings>,
+    image_packs: Res<ImagePacks>,
+    run_seed: Res<RunSeed>,
+) {
+    if !loading.pick_picture {
+        return;
+    }
+    loading.pick_picture = false;
+    
+    for entity in overlay_query.iter().chain(bg_sprite_query.iter()) {
+        commands.entity(entity).despawn();
+    }
+    let picture = choose_picture(&[], &settings, &image_packs.packs, 1, run_seed.seed);
+    println!("Loading background image: {}", picture);
+    let (handle, new_fog_mask) = spawn_level_background(&mut commands, &asset_server, &mut images, &picture);
+    *fog_mask = new_fog_mask;
+    bg_image.handle = handle;
+    bg_image.current_picture = picture;
+    bg_image.layout = None;
+}
+
+// Switch to a generated picture after this many image files fail to load in a row
+const MAX_IMAGE_LOAD_ATTEMPTS: usize = 5;
+
+#[allow(clippy::too_many_arguments)]
+fn handle_level_loading(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    mut images: ResMut<Assets<Image>>,
+    time: Res<Time>,
+    mut game_phase: ResMut<GamePhase>,
+    mut bg_image: ResMut<BackgroundImage>,
+    mut loading: ResMut<LevelLoading>,
+    mut image_warning: ResMut<ImageWarning>,
+    settings: Res<Settings>,
+    image_packs: Res<ImagePacks>,
+    game_state: Res<GameState>,
+    bg_sprite_query: Query<Entity, With<BackgroundSprite>>,
+) {
+    if *game_phase != GamePhase::Loading {
+        return;
+    }
+    
+    loading.elapsed += time.delta_seconds();
+    
+    // Generated pictures never go through the asset server; they're added to the image assets
+    // once painted, and until then there's no load state either way
+    if !images.contains(&bg_image.handle) {
+        match asset_server.get_load_state(&bg_image.handle) {
+            Some(LoadState::Failed(err)) => {
+                println!("⚠️  Failed to load {}: {}", bg_image.current_picture, err);
+                if let Some(failed) = bg_image.current_picture.file_path() {
+                    loading.failed_images.push(failed.to_string());
+                }
+                
+                // Swap in another picture and keep waiting, or fall back to a generated one
+                let replacement = if loading.failed_images.len() < MAX_IMAGE_LOAD_ATTEMPTS {
+                    match get_random_image_path(&loading.failed_images, &settings, &image_packs.packs, game_state.level) {
+                        Some(path) if !loading.failed_images.contains(&path) => PictureSource::File(path),
+                        _ => fallback_source(),
+                    }
+                } else {
+                    fallback_source()
+                };
+                println!("🔁 Trying another image: {}", replacement);
+                for entity in bg_sprite_query.iter() {
+                    commands.entity(entity).despawn();
+                }
+                bg_image.handle = spawn_background_sprite(&mut commands, &asset_server, &mut images, &replacement);
+                bg_image.current_picture = replacement;
+                bg_image.layout = None;
+                return;
+            }
+            _ => return, // Still loading
+        }
+    }
+    
+    println!("✅ Background image ready - go!");
+    
+    // Tell the player when they're not seeing the picture they should have
+    let is_file = bg_image.current_picture.file_path().is_some();
+    image_warning.message = if !loading.failed_images.is_empty() {
+        let instead = if is_file { "picked another" } else { "showing a generated picture" };
+        Some(format!("WARNING: Could not load {} - {}", loading.failed_images.join(", "), instead))
+    } else if !is_file && settings.pictures.generated <= 0.0 {
+        // Generated art wasn't asked for, so the folder must be empty
+        Some(format!("WARNING: No pictures found in {} - showing a generated picture", IMAGES_DIR))
+    } else {
+        None
+    };
+    
+    *loading = LevelLoading::default();
+    *game_phase = GamePhase::Playing;
+}
+
+fn update_image_warning(
+    image_warning: Res<ImageWarning>,
+    game_phase: Res<GamePhase>,
+    mut warning_query: Query<(&mut Text, &mut Visibility), With<ImageWarningText>>,
+) {
+    // Only relevant while a level is on screen
+    let in_level = *game_phase == GamePhase::Playing;
+    
+    for (mut text, mut visibility) in warning_query.iter_mut() {
+        match &image_warning.message {
+            Some(message) if in_level => {
+                if text.sections[0].value != *message {
+                    text.sections[0].value = message.clone();
+                }
+                *visibility = Visibility::Visible;
+            }
+            _ => *visibility = Visibility::Hidden,
+        }
     }
 }
 
@@ -1452,7 +1990,7 @@ fn handle_pause_and_mute(
     keyboard: Res<ButtonInput<KeyCode>>,
     mut game_state: ResMut<GameState>,
     game_phase: Res<GamePhase>,
-    mut audio: NonSendMut<AudioResource>,
+    mut mute_events: EventWriter<ToggleMute>,
 ) {
     // Only allow pause and mute during active gameplay
     if *game_phase != GamePhase::Playing {
@@ -1471,11 +2009,6 @@ fn handle_pause_and_mute(
     
     // Handle mute with M key (works anytime during gameplay)
     if keyboard.just_pressed(KeyCode::KeyM) {
-        audio.manager.toggle_mute();
-        if audio.manager.is_muted() {
-            println!("🔇 Audio MUTED");
-        } else {
-            println!("🔊 Audio UNMUTED");
-        }
+        mute_events.send(ToggleMute);
     }
 }
@@ -0,0 +1,273 @@
+use bevy::prelude::*;
+use rand::seq::SliceRandom;
+use serde::Deserialize;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::credits::track_credit;
+use crate::audio_plugin::{
+    AudioHandle, HoldEffects, NextTrack, PlayRequested, PreviousTrack, SetIntensity, SetMusicLevel, SetVolume,
+};
+use crate::settings::{Settings, VolumeChannel};
+use crate::grid::GameGrid;
+use crate::{world_to_grid, Enemy, GamePhase, GameState, Player};
+
+// Music tracks are the audio files directly in here (subfolders hold stem layers)
+const MUSIC_DIR: &str = "assets/sounds";
+// How long the "Now playing" toast stays up after the track changes
+const NOW_PLAYING_TOAST_TIME: f32 = 3.0;
+// Volume down/up keys for each volume
+const VOLUME_KEYS: [(VolumeChannel, KeyCode, KeyCode); 3] = [
+    (VolumeChannel::Master, KeyCode::Minus, KeyCode::Equal),
+    (VolumeChannel::Music, KeyCode::Comma, KeyCode::Period),
+    (VolumeChannel::Sfx, KeyCode::Semicolon, KeyCode::Quote),
+];
+// Enemies within this many cells of the line being drawn count as a threat
+const DANGER_DISTANCE: i32 = 5;
+// Below this many seconds left on the level timer the music gets tense
+const LOW_TIME_SECONDS: f32 = 20.0;
+// Music volume while the completed picture is on show, while paused, and on menu screens
+const LEVEL_COMPLETE_MUSIC_LEVEL: f32 = 0.4;
+const PAUSED_MUSIC_LEVEL: f32 = 0.25;
+const MENU_MUSIC_LEVEL: f32 = 0.5;
+
+// The part of a track's sidecar file (same name, .toml) that the music player reads,
+// e.g. mood = "calm". The rest is credits.
+#[derive(Default, Deserialize)]
+#[serde(default)]
+struct TrackInfo {
+    mood: Option<String>,
+    // Stems played in step with the track, brought in one by one as the game gets tense.
+    // Relative to the track's folder, e.g. "stems/leader_drums.mp3".
+    layers: Vec<String>,
+}
+
+fn track_info(track: &Path) -> Option<TrackInfo> {
+    let content = fs::read_to_string(track.with_extension("toml")).ok()?;
+    toml::from_str(&content).ok()
+}
+
+fn track_mood(track: &Path) -> Option<String> {
+    track_info(track)?.mood
+}
+
+pub fn track_layers(track: &Path) -> Vec<PathBuf> {
+    let folder = track.parent().unwrap_or(Path::new(""));
+    track_info(track)
+        .map(|info| info.layers.iter().map(|layer| folder.join(layer)).collect())
+        .unwrap_or_default()
+}
+
+// Track matching a level's music request: a file name (with or without extension),
+// otherwise a random track with that mood
+pub fn find_requested_track(request: &str, tracks: &[PathBuf]) -> Option<PathBuf> {
+    let by_name = tracks.iter().find(|track| {
+        [track.file_name(), track.file_stem()]
+            .into_iter()
+            .flatten()
+            .any(|name| name.eq_ignore_ascii_case(request))
+    });
+    if let Some(track) = by_name {
+        return Some(track.clone());
+    }
+
+    let with_mood: Vec<&PathBuf> = tracks
+        .iter()
+        .filter(|track| track_mood(track).is_some_and(|mood| mood.eq_ignore_ascii_case(request)))
+        .collect();
+    with_mood.choose(&mut rand::thread_rng()).map(|track| (*track).clone())
+}
+
+#[derive(Component)]
+pub struct NowPlayingToast;
+
+pub fn setup_now_playing_toast(mut commands: Commands) {
+    commands.spawn((
+        TextBundle::from_section(
+            "",
+            TextStyle {
+                font_size: 20.0,
+                color: Color::srgb(0.6, 1.0, 0.6),
+                ..default()
+            },
+        )
+        .with_style(Style {
+            position_type: PositionType::Absolute,
+            bottom: Val::Px(10.0),
+            right: Val::Px(10.0),
+            ..default()
+        }),
+        Visibility::Hidden,
+        NowPlayingToast,
+    ));
+}
+
+// Every file in the music folder that could be a track, sorted. There's no list of
+// extensions: whatever rodio can decode plays, and sync_tracks skips the rest with a
+// warning. Hidden files and credit sidecars (song.toml) are left out.
+pub fn list_music_files() -> Vec<PathBuf> {
+    let Ok(entries) = fs::read_dir(MUSIC_DIR) else {
+        return Vec::new();
+    };
+    let mut tracks: Vec<PathBuf> = entries
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| path.is_file())
+        .filter(|path| !path.file_name().unwrap_or_default().to_string_lossy().starts_with('.'))
+        .filter(|path| !path.extension().unwrap_or_default().eq_ignore_ascii_case("toml"))
+        .collect();
+    tracks.sort();
+    tracks
+}
+
+// Music plays at full volume during play and ducks under everything else
+fn music_level(game_phase: &GamePhase, game_state: &GameState) -> f32 {
+    match game_phase {
+        GamePhase::Playing | GamePhase::Loading if game_state.paused => PAUSED_MUSIC_LEVEL,
+        GamePhase::Playing | GamePhase::Loading if game_state.level_complete_timer.is_some() => {
+            LEVEL_COMPLETE_MUSIC_LEVEL
+        }
+        GamePhase::Playing | GamePhase::Loading => 1.0,
+        GamePhase::HighScoreScreen
+        | GamePhase::Settings
+        | GamePhase::Credits
+        | GamePhase::NameEntry
+        | GamePhase::ShowingNewScores => MENU_MUSIC_LEVEL,
+    }
+}
+
+// Duck the music and hold effects to match the game: quieter on menus and under the level
+// complete display, quietest and with effects held while paused
+pub fn follow_game_state(
+    game_phase: Res<GamePhase>,
+    game_state: Res<GameState>,
+    mut level_events: EventWriter<SetMusicLevel>,
+    mut hold_events: EventWriter<HoldEffects>,
+    mut sent: Local<Option<(f32, bool)>>,
+) {
+    let state = (music_level(&game_phase, &game_state), game_state.paused);
+    if *sent != Some(state) {
+        level_events.send(SetMusicLevel(state.0));
+        hold_events.send(HoldEffects(state.1));
+        *sent = Some(state);
+    }
+}
+
+// Crossfade to the music a new level asks for, handle the track and volume keys,
+// and show a toast whenever the track or a volume changes
+#[allow(clippy::too_many_arguments)]
+pub fn update_music(
+    keyboard: Res<ButtonInput<KeyCode>>,
+    time: Res<Time>,
+    game_phase: Res<GamePhase>,
+    game_state: Res<GameState>,
+    settings: Res<Settings>,
+    audio: Res<AudioHandle>,
+    (mut volume_events, mut next_events, mut previous_events, mut request_events): (
+        EventWriter<SetVolume>,
+        EventWriter<NextTrack>,
+        EventWriter<PreviousTrack>,
+        EventWriter<PlayRequested>,
+    ),
+    mut music_chosen_for: Local<Option<(u32, u32)>>,
+    mut shown_track: Local<Option<PathBuf>>,
+    mut toast_time: Local<f32>,
+    mut toast_query: Query<(&mut Text, &mut Visibility), With<NowPlayingToast>>,
+) {
+    let Ok((mut text, mut visibility)) = toast_query.get_single_mut() else {
+        return;
+    };
+
+    // Volume keys (not while typing a name)
+    if *game_phase != GamePhase::NameEntry {
+        for (channel, down, up) in VOLUME_KEYS {
+            let step = keyboard.just_pressed(up) as i32 - keyboard.just_pressed(down) as i32;
+            if step != 0 {
+                let volume = settings.audio.stepped_volume(channel, step);
+                volume_events.send(SetVolume { channel, volume });
+                text.sections[0].value = format!("{}: {:.0}%", channel.label(), volume * 100.0);
+                *toast_time = NOW_PLAYING_TOAST_TIME;
+            }
+        }
+    }
+
+    // Switch music when play starts on a new level or after a restart (even back on the same
+    // level), so the theme picked on the start screen counts too
+    let run_and_level = Some((game_state.run, game_state.level));
+    if *game_phase == GamePhase::Playing && *music_chosen_for != run_and_level {
+        *music_chosen_for = run_and_level;
+        if let Some(request) = settings.music_for_level(game_state.level) {
+            println!("🎵 Level {} asks for \"{}\"", game_state.level, request);
+            request_events.send(PlayRequested(request.to_string()));
+        }
+    }
+
+    // ] = next track, [ = previous track (not while typing a name)
+    if *game_phase != GamePhase::NameEntry {
+        if keyboard.just_pressed(KeyCode::BracketRight) {
+            next_events.send(NextTrack);
+        } else if keyboard.just_pressed(KeyCode::BracketLeft) {
+            previous_events.send(PreviousTrack);
+        }
+    }
+
+    let current = audio.current_track();
+    if current != *shown_track {
+        if let Some(track) = &current {
+            let credit = track_credit(track);
+            let mut line = format!("Now playing: {}", credit.title.unwrap_or_default());
+            if let Some(artist) = credit.credit {
+                line.push_str(&format!(" - {}", artist));
+            }
+            text.sections[0].value = line;
+            *toast_time = NOW_PLAYING_TOAST_TIME;
+        }
+        *shown_track = current;
+    }
+
+    *toast_time = (*toast_time - time.delta_seconds()).max(0.0);
+    *visibility = if *toast_time > 0.0 { Visibility::Visible } else { Visibility::Hidden };
+}
+
+// Drive the music's intensity from the danger the player is in: drawing a line, an enemy
+// close to the line, and time running out each bring in another stem layer
+pub fn update_music_intensity(
+    player_query: Query<&Player>,
+    enemy_query: Query<&Transform, With<Enemy>>,
+    grid: Res<GameGrid>,
+    game_state: Res<GameState>,
+    game_phase: Res<GamePhase>,
+    mut intensity_events: EventWriter<SetIntensity>,
+    mut sent: Local<Option<f32>>,
+) {
+    let playing = *game_phase == GamePhase::Playing
+        && !game_state.game_over
+        && !game_state.paused
+        && game_state.level_complete_timer.is_none();
+    let intensity = if playing { danger(&player_query, &enemy_query, &grid, &game_state) } else { 0.0 };
+    if *sent != Some(intensity) {
+        intensity_events.send(SetIntensity(intensity));
+        *sent = Some(intensity);
+    }
+}
+
+// Share of the threats the player currently faces, from 0.0 to 1.0
+fn danger(
+    player_query: &Query<&Player>,
+    enemy_query: &Query<&Transform, With<Enemy>>,
+    grid: &GameGrid,
+    game_state: &GameState,
+) -> f32 {
+    let drawing = player_query.get_single().map(|player| player.is_drawing).unwrap_or(false);
+    let enemy_near_line = drawing
+        && enemy_query.iter().any(|transform| {
+            let (x, y) = world_to_grid(transform.translation.truncate());
+            grid.drawing_path
+                .iter()
+                .any(|&(path_x, path_y)| (path_x - x).abs() <= DANGER_DISTANCE && (path_y - y).abs() <= DANGER_DISTANCE)
+        });
+    let low_time = game_state.level_timer < LOW_TIME_SECONDS;
+
+    let threats = [drawing, enemy_near_line, low_time].iter().filter(|&&threat| threat).count();
+    threats as f32 / 3.0
+}
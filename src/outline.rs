@@ -0,0 +1,121 @@
+use bevy::prelude::*;
+use std::collections::HashMap;
+
+use crate::grid::GameGrid;
+use crate::{CELL_SIZE, GRID_SIZE, WINDOW_HEIGHT, WINDOW_WIDTH};
+
+// Boundary loops of the claimed territory in world coordinates, rebuilt only when the grid changes
+#[derive(Resource, Default)]
+pub struct ClaimedOutline {
+    pub loops: Vec<Vec<Vec2>>,
+}
+
+pub fn update_claimed_outline(grid: Res<GameGrid>, mut outline: ResMut<ClaimedOutline>) {
+    if !grid.has_changes() {
+        return;
+    }
+
+    outline.loops = trace_outlines(&grid)
+        .into_iter()
+        .map(|points| points.into_iter().map(|(x, y)| vertex_to_world(x, y)).collect())
+        .collect();
+}
+
+fn vertex_to_world(x: i32, y: i32) -> Vec2 {
+    Vec2::new(
+        x as f32 * CELL_SIZE - WINDOW_WIDTH / 2.0,
+        y as f32 * CELL_SIZE - WINDOW_HEIGHT / 2.0,
+    )
+}
+
+// Unlike GameGrid::is_claimed, the outside of the grid is empty here so the border gets an outline too
+fn solid(grid: &GameGrid, x: i32, y: i32) -> bool {
+    x >= 0 && x < GRID_SIZE as i32 && y >= 0 && y < GRID_SIZE as i32 && grid.is_claimed(x, y)
+}
+
+// Trace claimed regions into closed polygons over the cell-corner lattice.
+// Every claimed/unclaimed boundary contributes a unit edge oriented with the claimed
+// side on its left, so each region's outer boundary runs counter-clockwise and holes
+// run clockwise. Edges are then chained into loops, looking at the 2x2 cell case at
+// each corner (marching squares) to decide where the boundary continues.
+fn trace_outlines(grid: &GameGrid) -> Vec<Vec<(i32, i32)>> {
+    let mut edges: HashMap<(i32, i32), Vec<(i32, i32)>> = HashMap::new();
+    let mut add_edge = |from: (i32, i32), to: (i32, i32)| edges.entry(from).or_default().push(to);
+
+    for (x, y) in grid.claimed_cells() {
+        let (x, y) = (x as i32, y as i32);
+        if !solid(grid, x, y - 1) {
+            add_edge((x, y), (x + 1, y)); // Bottom side, heading east
+        }
+        if !solid(grid, x + 1, y) {
+            add_edge((x + 1, y), (x + 1, y + 1)); // Right side, heading north
+        }
+        if !solid(grid, x, y + 1) {
+            add_edge((x + 1, y + 1), (x, y + 1)); // Top side, heading west
+        }
+        if !solid(grid, x - 1, y) {
+            add_edge((x, y + 1), (x, y)); // Left side, heading south
+        }
+    }
+
+    let mut loops = Vec::new();
+    while let Some(&start) = edges.keys().next() {
+        let mut points = vec![start];
+        let mut current = start;
+        let mut heading: Option<(i32, i32)> = None;
+
+        while let Some(next) = take_next_edge(&mut edges, current, heading) {
+            heading = Some((next.0 - current.0, next.1 - current.1));
+            current = next;
+            if current == start {
+                break;
+            }
+            points.push(current);
+        }
+
+        loops.push(simplify(points));
+    }
+    loops
+}
+
+// Pick the outgoing edge at a corner. A corner only has two outgoing edges in the
+// diagonal 2x2 case (two claimed cells touching at a point); turning left there keeps
+// the two regions as separate loops instead of merging them through the corner.
+fn take_next_edge(
+    edges: &mut HashMap<(i32, i32), Vec<(i32, i32)>>,
+    from: (i32, i32),
+    heading: Option<(i32, i32)>,
+) -> Option<(i32, i32)> {
+    let targets = edges.get_mut(&from)?;
+    let index = match heading {
+        Some((hx, hy)) if targets.len() > 1 => targets
+            .iter()
+            .enumerate()
+            .max_by_key(|(_, &(tx, ty))| hx * (ty - from.1) - hy * (tx - from.0))
+            .map(|(index, _)| index)
+            .unwrap_or(0),
+        _ => 0,
+    };
+    let next = targets.swap_remove(index);
+    if targets.is_empty() {
+        edges.remove(&from);
+    }
+    Some(next)
+}
+
+// Drop vertices that sit in the middle of a straight run so each side is one segment
+fn simplify(points: Vec<(i32, i32)>) -> Vec<(i32, i32)> {
+    let count = points.len();
+    if count < 3 {
+        return points;
+    }
+    (0..count)
+        .filter(|&i| {
+            let (px, py) = points[(i + count - 1) % count];
+            let (cx, cy) = points[i];
+            let (nx, ny) = points[(i + 1) % count];
+            (cx - px) * (ny - cy) - (cy - py) * (nx - cx) != 0
+        })
+        .map(|i| points[i])
+        .collect()
+}
@@ -0,0 +1,187 @@
+use bevy::prelude::*;
+use serde::Deserialize;
+use std::fs::{self, File};
+use std::io;
+use std::path::Path;
+
+// Picture packs are zip files dropped in here
+const PACKS_DIR: &str = "assets/packs";
+// Every pack needs this at the root of the zip
+const PACK_MANIFEST: &str = "pack.toml";
+// Pack pictures are extracted here (relative to assets/) the first time they're shown
+const EXTRACT_DIR: &str = "cache/packs";
+// Picture paths inside packs look like "packs/<zip file>/<path in zip>"
+const PACK_PATH_PREFIX: &str = "packs/";
+
+// Optional per-picture hint at how hard a picture is to play
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Difficulty {
+    Easy,
+    Normal,
+    Hard,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct PackManifest {
+    pub name: String,
+    #[serde(default)]
+    pub author: String,
+    #[serde(default)]
+    pub licence: String,
+    #[serde(default)]
+    pub images: Vec<PackImageInfo>,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct PackImageInfo {
+    pub file: String, // Path inside the zip
+    pub title: Option<String>,
+    pub credit: Option<String>,
+    pub difficulty: Option<Difficulty>,
+}
+
+#[derive(Clone, Debug)]
+pub struct ImagePack {
+    pub file_name: String, // e.g. "animals.zip" - identifies the pack in settings
+    pub manifest: PackManifest,
+    entries: Vec<String>, // Every PNG or JPEG in the zip
+}
+
+impl ImagePack {
+    // Picture path of an image in the pack, in the form the image selection uses
+    pub fn picture_path(&self, entry: &str) -> String {
+        format!("{}{}/{}", PACK_PATH_PREFIX, self.file_name, entry)
+    }
+
+    pub fn image_paths(&self) -> Vec<String> {
+        self.entries.iter().map(|entry| self.picture_path(entry)).collect()
+    }
+
+    pub fn image_info(&self, entry: &str) -> Option<&PackImageInfo> {
+        self.manifest.images.iter().find(|info| info.file == entry)
+    }
+}
+
+// Every valid pack in assets/packs, sorted by file name. Packs without a readable
+// manifest are skipped with a warning.
+fn installed_packs() -> Vec<ImagePack> {
+    let mut packs = Vec::new();
+    let Ok(entries) = fs::read_dir(PACKS_DIR) else {
+        return packs;
+    };
+
+    for entry in entries.flatten() {
+        let path = entry.path();
+        let is_zip = path
+            .extension()
+            .map(|extension| extension.eq_ignore_ascii_case("zip"))
+            .unwrap_or(false);
+        if !is_zip {
+            continue;
+        }
+        match read_pack(&path) {
+            Ok(pack) => packs.push(pack),
+            Err(err) => println!("⚠️  Skipping picture pack {}: {}", path.display(), err),
+        }
+    }
+
+    packs.sort_by(|a, b| a.file_name.cmp(&b.file_name));
+    packs
+}
+
+fn read_pack(path: &Path) -> Result<ImagePack, String> {
+    let file = File::open(path).map_err(|err| err.to_string())?;
+    let mut archive = zip::ZipArchive::new(file).map_err(|err| err.to_string())?;
+
+    let manifest = {
+        let mut manifest_file = archive
+            .by_name(PACK_MANIFEST)
+            .map_err(|_| format!("no {} in the zip", PACK_MANIFEST))?;
+        let content = io::read_to_string(&mut manifest_file).map_err(|err| err.to_string())?;
+        toml::from_str::<PackManifest>(&content).map_err(|err| format!("invalid {}: {}", PACK_MANIFEST, err))?
+    };
+
+    let mut entries = Vec::new();
+    for i in 0..archive.len() {
+        let Ok(file) = archive.by_index(i) else {
+            continue;
+        };
+        // enclosed_name rejects absolute paths and ".." so entries can't escape the extract folder
+        if !file.is_file() || file.enclosed_name().is_none() {
+            continue;
+        }
+        let name = file.name().to_string();
+        let lower = name.to_lowercase();
+        if lower.ends_with(".png") || lower.ends_with(".jpg") || lower.ends_with(".jpeg") {
+            entries.push(name);
+        }
+    }
+    entries.sort();
+
+    Ok(ImagePack {
+        file_name: path.file_name().unwrap_or_default().to_string_lossy().to_string(),
+        manifest,
+        entries,
+    })
+}
+
+// Split a pack picture path into the pack's zip file name and the path inside the zip
+pub fn split_pack_path(picture_path: &str) -> Option<(&str, &str)> {
+    picture_path.strip_prefix(PACK_PATH_PREFIX)?.split_once('/')
+}
+
+// Asset path the asset server can load for a picture: pack pictures are extracted from
+// their zip first (again if the zip is newer), other paths are returned unchanged
+pub fn resolve_picture_path(picture_path: &str) -> String {
+    let Some((zip_name, entry)) = split_pack_path(picture_path) else {
+        return picture_path.to_string();
+    };
+
+    let zip_path = Path::new(PACKS_DIR).join(zip_name);
+    let pack_stem = Path::new(zip_name).file_stem().unwrap_or_default().to_string_lossy();
+    let asset_path = format!("{}/{}/{}", EXTRACT_DIR, pack_stem, entry);
+    let target = Path::new("assets").join(&asset_path);
+
+    let modified = |path: &Path| fs::metadata(path).and_then(|metadata| metadata.modified()).ok();
+    let up_to_date = matches!((modified(&target), modified(&zip_path)), (Some(extracted), Some(zip)) if extracted >= zip);
+    if !up_to_date {
+        if let Err(err) = extract_entry(&zip_path, entry, &target) {
+            println!("⚠️  Could not extract {} from {}: {}", entry, zip_path.display(), err);
+        }
+    }
+    asset_path
+}
+
+fn extract_entry(zip_path: &Path, entry: &str, target: &Path) -> Result<(), String> {
+    let file = File::open(zip_path).map_err(|err| err.to_string())?;
+    let mut archive = zip::ZipArchive::new(file).map_err(|err| err.to_string())?;
+    let mut source = archive.by_name(entry).map_err(|err| err.to_string())?;
+
+    let dir = target.parent().ok_or("extract path has no directory")?;
+    fs::create_dir_all(dir).map_err(|err| err.to_string())?;
+
+    // Write to a temporary name first so a half-written file is never loaded
+    let file_name = target.file_name().unwrap_or_default().to_string_lossy();
+    let partial = dir.join(format!(".{}.partial", file_name));
+    let mut output = File::create(&partial).map_err(|err| err.to_string())?;
+    io::copy(&mut source, &mut output).map_err(|err| err.to_string())?;
+    fs::rename(&partial, target).map_err(|err| err.to_string())
+}
+
+// Packs found at startup. Pictures, credits and the start screen's pack list all come from here,
+// so the zips are only read once.
+#[derive(Resource)]
+pub struct ImagePacks {
+    pub packs: Vec<ImagePack>,
+}
+
+impl ImagePacks {
+    pub fn load() -> Self {
+        let packs = installed_packs();
+        if !packs.is_empty() {
+            println!("📦 Found {} picture pack(s)", packs.len());
+        }
+        ImagePacks { packs }
+    }
+}
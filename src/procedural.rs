@@ -0,0 +1,334 @@
+use bevy::prelude::*;
+use bevy::render::render_asset::RenderAssetUsages;
+use bevy::render::render_resource::{Extent3d, TextureDimension, TextureFormat};
+use bevy::tasks::{block_on, poll_once, AsyncComputeTaskPool, Task};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+// Generated pictures are rendered at this size and scaled up to the window
+const PICTURE_WIDTH: usize = 400;
+const PICTURE_HEIGHT: usize = 300;
+const PICTURE_ASPECT: f32 = PICTURE_WIDTH as f32 / PICTURE_HEIGHT as f32;
+// Points plotted for a fractal flame; more gives smoother, brighter flames
+const FLAME_ITERATIONS: usize = 600_000;
+
+// Kinds of abstract art the generator can paint
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ArtStyle {
+    NoiseField, // Domain-warped noise with flowing contour bands
+    Voronoi, // Stained-glass cells around random points
+    FractalFlame, // Iterated function system plotted with log-density colouring
+}
+
+impl ArtStyle {
+    pub const ALL: [ArtStyle; 3] = [ArtStyle::NoiseField, ArtStyle::Voronoi, ArtStyle::FractalFlame];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            ArtStyle::NoiseField => "noise field",
+            ArtStyle::Voronoi => "Voronoi",
+            ArtStyle::FractalFlame => "fractal flame",
+        }
+    }
+}
+
+// A picture being painted on the async compute pool; it's added to the image assets
+// under `handle` once it's done
+#[derive(Component)]
+pub struct PaintingPicture {
+    handle: Handle<Image>,
+    task: Task<Image>,
+}
+
+// Paint a picture off the main thread (a fractal flame takes far longer than a frame).
+// The returned handle has no image behind it until the painting is finished.
+pub fn paint_in_background(
+    commands: &mut Commands,
+    images: &Assets<Image>,
+    paint: impl FnOnce() -> Image + Send + 'static,
+) -> Handle<Image> {
+    let handle = images.reserve_handle();
+    let task = AsyncComputeTaskPool::get().spawn(async move { paint() });
+    commands.spawn(PaintingPicture {
+        handle: handle.clone(),
+        task,
+    });
+    handle
+}
+
+pub fn finish_paintings(
+    mut commands: Commands,
+    mut images: ResMut<Assets<Image>>,
+    mut paintings: Query<(Entity, &mut PaintingPicture)>,
+) {
+    for (entity, mut painting) in paintings.iter_mut() {
+        if let Some(image) = block_on(poll_once(&mut painting.task)) {
+            images.insert(&painting.handle, image);
+            commands.entity(entity).despawn();
+        }
+    }
+}
+
+// Seed for one level's picture, derived from the run seed so a run can be replayed
+pub fn level_seed(run_seed: u64, level: u32) -> u64 {
+    // SplitMix64 finaliser - neighbouring levels get unrelated seeds
+    let mut z = run_seed.wrapping_add((level as u64).wrapping_mul(0x9e37_79b9_7f4a_7c15));
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    z ^ (z >> 31)
+}
+
+// Paint a picture in the given style. The same style and seed always give the same picture.
+pub fn generated_picture(style: ArtStyle, seed: u64) -> Image {
+    let mut rng = StdRng::seed_from_u64(seed);
+    match style {
+        ArtStyle::NoiseField => noise_field(&mut rng),
+        ArtStyle::Voronoi => voronoi(&mut rng),
+        ArtStyle::FractalFlame => fractal_flame(&mut rng),
+    }
+}
+
+// Built-in picture used when no image file can be shown: a diagonal gradient between
+// two random hues with soft value noise on top, so it's never a flat colour
+pub fn fallback_picture(seed: u64) -> Image {
+    let mut rng = StdRng::seed_from_u64(seed);
+    let from = random_hue(&mut rng);
+    let to = random_hue(&mut rng);
+    let noise = ValueNoise::new(&mut rng, 8);
+
+    render_picture(|u, v| {
+        let t = (u + v) / 2.0;
+        let shade = 0.75 + 0.5 * (noise.fractal(u, v, 3) - 0.5);
+        from.lerp(to, t) * shade
+    })
+}
+
+fn noise_field(rng: &mut StdRng) -> Image {
+    let palette = Palette::random(rng);
+    let warp_x = ValueNoise::new(rng, 6);
+    let warp_y = ValueNoise::new(rng, 6);
+    let noise = ValueNoise::new(rng, 8);
+    let warp_strength = rng.gen_range(0.3..0.9);
+    let bands = rng.gen_range(20.0..50.0);
+
+    render_picture(|u, v| {
+        // Push the sample point around with two other noise fields so the shapes swirl
+        let u = u + (warp_x.fractal(u, v, 3) - 0.5) * warp_strength;
+        let v = v + (warp_y.fractal(u, v, 3) - 0.5) * warp_strength;
+        let n = noise.fractal(u, v, 4);
+        // Value noise clusters around 0.5 - stretch it to use the whole palette
+        let t = ((n - 0.5) * 2.5 + 0.5).clamp(0.0, 1.0);
+        palette.at(t) * (0.9 + 0.1 * (n * bands).sin())
+    })
+}
+
+fn voronoi(rng: &mut StdRng) -> Image {
+    let palette = Palette::random(rng);
+    let sites: Vec<(Vec2, Vec3)> = (0..rng.gen_range(12..32))
+        .map(|_| {
+            let position = Vec2::new(rng.gen_range(0.0..PICTURE_ASPECT), rng.gen());
+            (position, palette.at(rng.gen()))
+        })
+        .collect();
+
+    render_picture(|u, v| {
+        let point = Vec2::new(u * PICTURE_ASPECT, v);
+        let mut nearest = (f32::MAX, Vec3::ZERO);
+        let mut second = f32::MAX;
+        for &(site, color) in &sites {
+            let distance = point.distance(site);
+            if distance < nearest.0 {
+                second = nearest.0;
+                nearest = (distance, color);
+            } else if distance < second {
+                second = distance;
+            }
+        }
+        // Dark leading between cells, and each cell lighter towards its centre
+        let edge = ((second - nearest.0) * 60.0).clamp(0.0, 1.0);
+        nearest.1 * (0.25 + 0.75 * edge) * (1.1 - nearest.0).clamp(0.6, 1.0)
+    })
+}
+
+struct FlameTransform {
+    affine: [f32; 6],
+    variation: usize,
+    color: Vec3,
+}
+
+impl FlameTransform {
+    fn apply(&self, p: Vec2) -> Vec2 {
+        let [a, b, c, d, e, f] = self.affine;
+        let q = Vec2::new(a * p.x + b * p.y + c, d * p.x + e * p.y + f);
+        let r2 = q.length_squared().max(1e-6);
+        match self.variation {
+            0 => Vec2::new(q.x.sin(), q.y.sin()), // Sinusoidal
+            1 => q / r2, // Spherical
+            2 => Vec2::new(q.x * r2.sin() - q.y * r2.cos(), q.x * r2.cos() + q.y * r2.sin()), // Swirl
+            _ => Vec2::new((q.x - q.y) * (q.x + q.y), 2.0 * q.x * q.y) / r2.sqrt(), // Horseshoe
+        }
+    }
+}
+
+fn fractal_flame(rng: &mut StdRng) -> Image {
+    let palette = Palette::random(rng);
+    let transforms: Vec<FlameTransform> = (0..rng.gen_range(3..=5))
+        .map(|_| FlameTransform {
+            affine: [(); 6].map(|_| rng.gen_range(-1.0..1.0)),
+            variation: rng.gen_range(0..4),
+            color: palette.at(rng.gen()),
+        })
+        .collect();
+
+    // Chaos game: jump between transforms at random and count where the point lands
+    let mut hits = vec![0.0f32; PICTURE_WIDTH * PICTURE_HEIGHT];
+    let mut colors = vec![Vec3::ZERO; PICTURE_WIDTH * PICTURE_HEIGHT];
+    let mut point = Vec2::new(rng.gen_range(-1.0..1.0), rng.gen_range(-1.0..1.0));
+    let mut color = Vec3::ZERO;
+    let extent = Vec2::new(PICTURE_ASPECT, 1.0) * 1.2;
+    for i in 0..FLAME_ITERATIONS {
+        let transform = &transforms[rng.gen_range(0..transforms.len())];
+        point = transform.apply(point);
+        color = (color + transform.color) / 2.0;
+        if !point.is_finite() || point.length_squared() > 1e6 {
+            point = Vec2::new(rng.gen_range(-1.0..1.0), rng.gen_range(-1.0..1.0));
+            continue;
+        }
+        // The first few points haven't settled onto the attractor yet
+        if i < 20 {
+            continue;
+        }
+        let uv = (point / extent + Vec2::ONE) / 2.0;
+        if (0.0..1.0).contains(&uv.x) && (0.0..1.0).contains(&uv.y) {
+            let pixel = (uv.y * PICTURE_HEIGHT as f32) as usize * PICTURE_WIDTH + (uv.x * PICTURE_WIDTH as f32) as usize;
+            hits[pixel] += 1.0;
+            colors[pixel] += color;
+        }
+    }
+
+    // Log-density tone mapping keeps both the sparse wisps and the dense core visible
+    let max_hits = hits.iter().copied().fold(1.0, f32::max);
+    let background = palette.at(0.0) * 0.08;
+    let pixels = hits
+        .iter()
+        .zip(&colors)
+        .map(|(&count, &sum)| {
+            if count == 0.0 {
+                return background;
+            }
+            let brightness = ((1.0 + count).ln() / (1.0 + max_hits).ln()).powf(1.0 / 2.2);
+            background.lerp(sum / count, brightness)
+        })
+        .collect();
+    image_from_pixels(pixels)
+}
+
+// Three related colours to paint with: a base hue, a neighbour and a contrasting accent
+struct Palette {
+    colors: [Vec3; 3],
+}
+
+impl Palette {
+    fn random(rng: &mut StdRng) -> Self {
+        let hue = rng.gen_range(0.0..360.0);
+        Palette {
+            colors: [
+                hsl(hue, 0.6, 0.2),
+                hsl(hue + rng.gen_range(20.0..60.0), 0.7, 0.5),
+                hsl(hue + 180.0, 0.8, 0.75),
+            ],
+        }
+    }
+
+    fn at(&self, t: f32) -> Vec3 {
+        let t = t.clamp(0.0, 1.0) * 2.0;
+        if t < 1.0 {
+            self.colors[0].lerp(self.colors[1], t)
+        } else {
+            self.colors[1].lerp(self.colors[2], t - 1.0)
+        }
+    }
+}
+
+fn hsl(hue: f32, saturation: f32, lightness: f32) -> Vec3 {
+    let color = Color::hsl(hue.rem_euclid(360.0), saturation, lightness).to_srgba();
+    Vec3::new(color.red, color.green, color.blue)
+}
+
+fn random_hue(rng: &mut StdRng) -> Vec3 {
+    hsl(rng.gen_range(0.0..360.0), 0.6, 0.5)
+}
+
+// Fill a picture by evaluating `shade` at every pixel's (u, v) in 0..1, v pointing down
+fn render_picture(shade: impl Fn(f32, f32) -> Vec3) -> Image {
+    let pixels = (0..PICTURE_WIDTH * PICTURE_HEIGHT)
+        .map(|i| {
+            let (x, y) = (i % PICTURE_WIDTH, i / PICTURE_WIDTH);
+            shade(x as f32 / PICTURE_WIDTH as f32, y as f32 / PICTURE_HEIGHT as f32)
+        })
+        .collect();
+    image_from_pixels(pixels)
+}
+
+// Pack row-major sRGB colours (top row first) into an 8-bit RGBA image
+fn image_from_pixels(pixels: Vec<Vec3>) -> Image {
+    let mut data = Vec::with_capacity(PICTURE_WIDTH * PICTURE_HEIGHT * 4);
+    for color in pixels {
+        let color = color.clamp(Vec3::ZERO, Vec3::ONE) * 255.0;
+        data.extend_from_slice(&[color.x as u8, color.y as u8, color.z as u8, 255]);
+    }
+
+    Image::new(
+        Extent3d {
+            width: PICTURE_WIDTH as u32,
+            height: PICTURE_HEIGHT as u32,
+            depth_or_array_layers: 1,
+        },
+        TextureDimension::D2,
+        data,
+        TextureFormat::Rgba8UnormSrgb,
+        RenderAssetUsages::default(),
+    )
+}
+
+// Smoothly interpolated random lattice, wrapping at the edges
+struct ValueNoise {
+    size: usize,
+    values: Vec<f32>,
+}
+
+impl ValueNoise {
+    fn new(rng: &mut StdRng, size: usize) -> Self {
+        ValueNoise {
+            size,
+            values: (0..size * size).map(|_| rng.gen()).collect(),
+        }
+    }
+
+    fn sample(&self, u: f32, v: f32) -> f32 {
+        let x = (u * self.size as f32).rem_euclid(self.size as f32);
+        let y = (v * self.size as f32).rem_euclid(self.size as f32);
+        let (x0, y0) = (x.floor() as usize, y.floor() as usize);
+        let smooth = |t: f32| t * t * (3.0 - 2.0 * t);
+        let (tx, ty) = (smooth(x.fract()), smooth(y.fract()));
+        let at = |x: usize, y: usize| self.values[(y % self.size) * self.size + x % self.size];
+
+        let top = at(x0, y0) + (at(x0 + 1, y0) - at(x0, y0)) * tx;
+        let bottom = at(x0, y0 + 1) + (at(x0 + 1, y0 + 1) - at(x0, y0 + 1)) * tx;
+        top + (bottom - top) * ty
+    }
+
+    // Sum of octaves at doubling frequency and halving amplitude, normalised to 0..1
+    fn fractal(&self, u: f32, v: f32, octaves: u32) -> f32 {
+        let mut total = 0.0;
+        let mut amplitude = 1.0;
+        let mut weight = 0.0;
+        for octave in 0..octaves {
+            let frequency = (1 << octave) as f32;
+            total += self.sample(u * frequency, v * frequency) * amplitude;
+            weight += amplitude;
+            amplitude /= 2.0;
+        }
+        total / weight
+    }
+}
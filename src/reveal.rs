@@ -0,0 +1,175 @@
+use bevy::prelude::*;
+use bevy::render::render_resource::TextureFormat;
+use std::f32::consts::TAU;
+
+use crate::background::BackgroundLayout;
+use crate::fog::{FogMask, FOG_CLAIMED, FOG_REVEALED, FOG_UNCLAIMED};
+use crate::grid::GameGrid;
+use crate::settings::{RevealAnimationStyle, RevealMode, Settings};
+use crate::{grid_to_world, BackgroundImage, FogOverlay, GRID_SIZE, LEVEL_DISPLAY_TIME, WINDOW_HEIGHT, WINDOW_WIDTH};
+
+// Length of the level-complete reveal animation (well inside LEVEL_DISPLAY_TIME)
+const REVEAL_ANIMATION_TIME: f32 = 2.5;
+const _: () = assert!(REVEAL_ANIMATION_TIME < LEVEL_DISPLAY_TIME);
+// Width of the soft edge on wave and wipe fronts, as a fraction of the full sweep
+const REVEAL_EDGE_SOFTNESS: f32 = 0.15;
+// Block sizes (in cells) the pixelated picture steps through before turning sharp
+const PIXELATE_BLOCK_SIZES: [usize; 5] = [16, 8, 4, 2, 1];
+
+struct ActiveReveal {
+    style: RevealAnimationStyle,
+    elapsed: f32,
+    origin: Vec2, // Grid coordinates the wave spreads out from
+}
+
+#[derive(Resource, Default)]
+pub struct RevealAnimation {
+    active: Option<ActiveReveal>,
+}
+
+impl RevealAnimation {
+    pub fn start(&mut self, style: RevealAnimationStyle, origin: Vec2) {
+        self.active = Some(ActiveReveal {
+            style,
+            elapsed: 0.0,
+            origin,
+        });
+    }
+
+    pub fn stop(&mut self) {
+        self.active = None;
+    }
+}
+
+// Grid cell at the middle of the visible playfield (the grid extends above the window)
+pub fn visible_centre() -> Vec2 {
+    Vec2::new(GRID_SIZE as f32 / 2.0, WINDOW_HEIGHT / WINDOW_WIDTH * GRID_SIZE as f32 / 2.0)
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn animate_reveal(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut animation: ResMut<RevealAnimation>,
+    fog_mask: Res<FogMask>,
+    bg_image: Res<BackgroundImage>,
+    grid: Res<GameGrid>,
+    settings: Res<Settings>,
+    mut images: ResMut<Assets<Image>>,
+    overlay_query: Query<Entity, With<FogOverlay>>,
+) {
+    let Some(reveal) = animation.active.as_mut() else {
+        return;
+    };
+
+    reveal.elapsed += time.delta_seconds();
+    let progress = (reveal.elapsed / REVEAL_ANIMATION_TIME).min(1.0);
+
+    if progress >= 1.0 {
+        for entity in overlay_query.iter() {
+            commands.entity(entity).despawn();
+        }
+        animation.stop();
+        println!("✅ Reveal animation finished - image is fully visible!");
+        return;
+    }
+
+    let style = reveal.style;
+    let origin = reveal.origin;
+    let pixels = match style {
+        RevealAnimationStyle::Pixelate => bg_image
+            .layout
+            .and_then(|layout| pixelated_pixels(&images, &bg_image.handle, &layout, progress)),
+        _ => None,
+    };
+
+    let Some(mask) = images.get_mut(&fog_mask.handle) else {
+        return;
+    };
+
+    if let Some(pixels) = pixels {
+        mask.data.copy_from_slice(&pixels);
+        return;
+    }
+
+    // Wave and wipe (and pixelate without usable picture data) fade cells out along a front
+    let max_distance = [
+        Vec2::ZERO,
+        Vec2::new(GRID_SIZE as f32, 0.0),
+        Vec2::new(0.0, GRID_SIZE as f32),
+        Vec2::splat(GRID_SIZE as f32),
+    ]
+    .iter()
+    .map(|corner| corner.distance(origin))
+    .fold(1.0, f32::max);
+    let centre = visible_centre();
+    let front = progress * (1.0 + REVEAL_EDGE_SOFTNESS);
+
+    for y in 0..GRID_SIZE {
+        for x in 0..GRID_SIZE {
+            let cell = Vec2::new(x as f32 + 0.5, y as f32 + 0.5);
+            // Position of the cell along the sweep, 0 = revealed first, 1 = revealed last
+            let order = match style {
+                RevealAnimationStyle::RadialWipe => {
+                    // Clockwise from 12 o'clock
+                    let offset = cell - centre;
+                    offset.x.atan2(offset.y).rem_euclid(TAU) / TAU
+                }
+                _ => cell.distance(origin) / max_distance,
+            };
+            let revealed = ((front - order) / REVEAL_EDGE_SOFTNESS).clamp(0.0, 1.0);
+
+            let mut color = if !grid.is_claimed(x as i32, y as i32) {
+                FOG_UNCLAIMED
+            } else if settings.reveal_mode == RevealMode::Progressive {
+                FOG_REVEALED
+            } else {
+                FOG_CLAIMED
+            };
+            color[3] = (color[3] as f32 * (1.0 - revealed)) as u8;
+
+            let pixel = ((GRID_SIZE - 1 - y) * GRID_SIZE + x) * 4;
+            mask.data[pixel..pixel + 4].copy_from_slice(&color);
+        }
+    }
+}
+
+// Build mask pixels showing the background picture in blocks that shrink as the
+// animation progresses. Returns None if the picture isn't loaded as 8-bit RGBA.
+fn pixelated_pixels(
+    images: &Assets<Image>,
+    background: &Handle<Image>,
+    layout: &BackgroundLayout,
+    progress: f32,
+) -> Option<Vec<u8>> {
+    let picture = images.get(background)?;
+    if !matches!(
+        picture.texture_descriptor.format,
+        TextureFormat::Rgba8UnormSrgb | TextureFormat::Rgba8Unorm
+    ) {
+        return None;
+    }
+
+    let stage = ((progress * PIXELATE_BLOCK_SIZES.len() as f32) as usize).min(PIXELATE_BLOCK_SIZES.len() - 1);
+    let block = PIXELATE_BLOCK_SIZES[stage];
+    let width = picture.width() as usize;
+
+    let mut pixels = vec![0; GRID_SIZE * GRID_SIZE * 4];
+    for y in 0..GRID_SIZE {
+        for x in 0..GRID_SIZE {
+            // Sample the picture at the centre of this cell's block
+            let block_x = (x / block) * block + block / 2;
+            let block_y = (y / block) * block + block / 2;
+            let world = grid_to_world(block_x.min(GRID_SIZE - 1) as i32, block_y.min(GRID_SIZE - 1) as i32);
+            let Some(source) = layout.image_pixel_at(world) else {
+                continue; // Not covered by the picture - leave transparent
+            };
+
+            let source = (source.y as usize * width + source.x as usize) * 4;
+            let pixel = ((GRID_SIZE - 1 - y) * GRID_SIZE + x) * 4;
+            pixels[pixel..pixel + 3].copy_from_slice(&picture.data[source..source + 3]);
+            pixels[pixel + 3] = 255;
+        }
+    }
+    Some(pixels)
+}
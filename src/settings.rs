@@ -0,0 +1,445 @@
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+
+use crate::sound_bank::{GameSound, PlaySfx};
+use crate::GamePhase;
+
+const SETTINGS_FILE: &str = "settings.toml";
+// Where older versions kept the mute state, as the bare text true or false
+const OLD_MUTE_FILE: &str = "mute_config.json";
+// How much the volume keys and the settings screen change a volume by
+const VOLUME_STEP: f32 = 0.1;
+
+// How claimed cells look before the reveal threshold is reached
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RevealMode {
+    #[default]
+    Classic, // Claimed cells turn grey, picture appears all at once at the threshold
+    Progressive, // Claimed cells turn transparent, picture appears piece by piece
+}
+
+impl RevealMode {
+    fn label(self) -> &'static str {
+        match self {
+            RevealMode::Classic => "Classic (all at once)",
+            RevealMode::Progressive => "Progressive (piece by piece)",
+        }
+    }
+
+    fn cycle(self, step: i32) -> Self {
+        cycle_in(&[RevealMode::Classic, RevealMode::Progressive], self, step)
+    }
+}
+
+// Animation played on the fog overlay once the reveal threshold is reached
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RevealAnimationStyle {
+    Instant, // Overlay disappears in a single frame
+    #[default]
+    Wave, // Dissolves outward from the last captured area
+    RadialWipe, // Clock-hand sweep around the centre of the playfield
+    Pixelate, // Blocky version of the picture sharpening into the real one
+}
+
+impl RevealAnimationStyle {
+    fn label(self) -> &'static str {
+        match self {
+            RevealAnimationStyle::Instant => "Instant",
+            RevealAnimationStyle::Wave => "Wave from last capture",
+            RevealAnimationStyle::RadialWipe => "Radial wipe",
+            RevealAnimationStyle::Pixelate => "Pixelated to sharp",
+        }
+    }
+
+    fn cycle(self, step: i32) -> Self {
+        cycle_in(
+            &[
+                RevealAnimationStyle::Instant,
+                RevealAnimationStyle::Wave,
+                RevealAnimationStyle::RadialWipe,
+                RevealAnimationStyle::Pixelate,
+            ],
+            self,
+            step,
+        )
+    }
+}
+
+// How background pictures are fitted to the 800x600 window
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FitMode {
+    Stretch, // Fill the window, distorting the picture if its aspect ratio differs
+    #[default]
+    Cover, // Fill the window keeping proportions, cropping the centre
+    Contain, // Show the whole picture keeping proportions, with letterbox bars
+    SmartCrop, // Like cover, but crop where the picture has the most detail
+}
+
+impl FitMode {
+    fn label(self) -> &'static str {
+        match self {
+            FitMode::Stretch => "Stretch",
+            FitMode::Cover => "Cover (centre crop)",
+            FitMode::Contain => "Contain (letterbox)",
+            FitMode::SmartCrop => "Smart crop",
+        }
+    }
+
+    fn cycle(self, step: i32) -> Self {
+        cycle_in(&[FitMode::Stretch, FitMode::Cover, FitMode::Contain, FitMode::SmartCrop], self, step)
+    }
+}
+
+// Step forwards or backwards through a list of options, wrapping at either end
+fn cycle_in<T: Copy + PartialEq>(options: &[T], current: T, step: i32) -> T {
+    let index = options.iter().position(|&option| option == current).unwrap_or(0) as i32;
+    options[(index + step).rem_euclid(options.len() as i32) as usize]
+}
+
+// Crossfade lengths offered on the settings screen, in seconds (0 = cut straight over)
+const MUSIC_FADE_OPTIONS: &[f32] = &[0.0, 0.5, 1.0, 2.0, 3.0, 5.0];
+
+// Relative chance of a level using a picture from assets/images versus generated art.
+// Only set in settings.toml, e.g. folder = 3, generated = 1 for one generated level in four.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PictureWeights {
+    pub folder: f32,
+    pub generated: f32,
+}
+
+impl Default for PictureWeights {
+    fn default() -> Self {
+        PictureWeights {
+            folder: 1.0,
+            generated: 0.0, // Generated art only when the folder is empty
+        }
+    }
+}
+
+// When effect sounds are generated by the synth instead of played from files
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SynthEffects {
+    Off, // Only files from the sound bank
+    #[default]
+    MissingFiles, // For events the sound bank has no file for
+    Always, // Never use effect files
+}
+
+impl SynthEffects {
+    fn label(self) -> &'static str {
+        match self {
+            SynthEffects::Off => "Off",
+            SynthEffects::MissingFiles => "When no file",
+            SynthEffects::Always => "Always",
+        }
+    }
+
+    fn cycle(self, step: i32) -> Self {
+        cycle_in(&[SynthEffects::Off, SynthEffects::MissingFiles, SynthEffects::Always], self, step)
+    }
+}
+
+// Volume levels from 0.0 to 1.0. Music plays at master * music, effects at master * sfx.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AudioSettings {
+    pub muted: bool,
+    pub master_volume: f32,
+    pub music_volume: f32,
+    pub sfx_volume: f32,
+    pub synth_effects: SynthEffects,
+}
+
+impl Default for AudioSettings {
+    fn default() -> Self {
+        AudioSettings {
+            muted: false,
+            master_volume: 1.0,
+            music_volume: 1.0,
+            sfx_volume: 1.0,
+            synth_effects: SynthEffects::default(),
+        }
+    }
+}
+
+// Which volume a volume key or settings row changes
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum VolumeChannel {
+    Master,
+    Music,
+    Sfx,
+}
+
+impl VolumeChannel {
+    pub fn label(self) -> &'static str {
+        match self {
+            VolumeChannel::Master => "Master volume",
+            VolumeChannel::Music => "Music volume",
+            VolumeChannel::Sfx => "Effects volume",
+        }
+    }
+}
+
+impl AudioSettings {
+    pub fn volume(&self, channel: VolumeChannel) -> f32 {
+        match channel {
+            VolumeChannel::Master => self.master_volume,
+            VolumeChannel::Music => self.music_volume,
+            VolumeChannel::Sfx => self.sfx_volume,
+        }
+    }
+
+    pub fn set_volume(&mut self, channel: VolumeChannel, volume: f32) {
+        let volume = volume.clamp(0.0, 1.0);
+        match channel {
+            VolumeChannel::Master => self.master_volume = volume,
+            VolumeChannel::Music => self.music_volume = volume,
+            VolumeChannel::Sfx => self.sfx_volume = volume,
+        }
+    }
+
+    // A volume raised or lowered by whole steps, staying within 0% to 100%
+    pub fn stepped_volume(&self, channel: VolumeChannel, step: i32) -> f32 {
+        // Round to the nearest step so hand-edited values line up again
+        let steps = (self.volume(channel) / VOLUME_STEP).round() + step as f32;
+        (steps * VOLUME_STEP).clamp(0.0, 1.0)
+    }
+
+    pub fn adjust_volume(&mut self, channel: VolumeChannel, step: i32) {
+        self.set_volume(channel, self.stepped_volume(channel, step));
+    }
+}
+
+// Player preferences, persisted to settings.toml whenever they change
+#[derive(Resource, Clone, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Settings {
+    pub reveal_mode: RevealMode,
+    pub reveal_animation: RevealAnimationStyle,
+    pub fit_mode: FitMode,
+    // Picture theme (subfolder of assets/images) for the whole run; None = all pictures.
+    // Picked with T on the start screen.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub theme: Option<String>,
+    // Picture packs (zip file names in assets/packs) the player switched off on the start screen
+    pub disabled_packs: Vec<String>,
+    // How long music crossfades last when the track changes
+    pub music_fade_seconds: f32,
+    pub audio: AudioSettings,
+    pub pictures: PictureWeights,
+    // Themes bound to particular levels, overriding `theme` - e.g. "5" = "animals", or "all"
+    pub level_themes: BTreeMap<String, String>,
+    // Music a level asks for: a file name in assets/sounds or a mood from the tracks'
+    // sidecar files, e.g. "10" = "calm"
+    pub level_music: BTreeMap<String, String>,
+    // Music a picture theme asks for, used when the level doesn't ask for any
+    pub theme_music: BTreeMap<String, String>,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Settings {
+            reveal_mode: RevealMode::default(),
+            reveal_animation: RevealAnimationStyle::default(),
+            fit_mode: FitMode::default(),
+            theme: None,
+            disabled_packs: Vec::new(),
+            music_fade_seconds: 2.0,
+            audio: AudioSettings::default(),
+            pictures: PictureWeights::default(),
+            level_themes: BTreeMap::new(),
+            level_music: BTreeMap::new(),
+            theme_music: BTreeMap::new(),
+        }
+    }
+}
+
+impl Settings {
+    pub fn load() -> Self {
+        let mut settings: Settings = fs::read_to_string(SETTINGS_FILE)
+            .ok()
+            .and_then(|content| match toml::from_str(&content) {
+                Ok(settings) => Some(settings),
+                Err(err) => {
+                    println!("⚠️  Ignoring invalid {}: {}", SETTINGS_FILE, err);
+                    None
+                }
+            })
+            .unwrap_or_default();
+        settings.migrate_mute_file();
+        settings
+    }
+
+    // Move the mute state from the old mute file into settings.toml, then remove the old file
+    fn migrate_mute_file(&mut self) {
+        let Ok(content) = fs::read_to_string(OLD_MUTE_FILE) else {
+            return;
+        };
+        self.audio.muted = content.trim() == "true";
+        self.save();
+        if fs::remove_file(OLD_MUTE_FILE).is_ok() {
+            println!("🔧 Moved mute setting from {} to {}", OLD_MUTE_FILE, SETTINGS_FILE);
+        }
+    }
+
+    // Theme to draw a level's picture from, or None for all pictures
+    pub fn theme_for_level(&self, level: u32) -> Option<&str> {
+        match self.level_themes.get(&level.to_string()) {
+            Some(theme) if theme == "all" => None,
+            Some(theme) => Some(theme),
+            None => self.theme.as_deref(),
+        }
+    }
+
+    // Track or mood requested for a level, by the level itself or its picture theme
+    pub fn music_for_level(&self, level: u32) -> Option<&str> {
+        self.level_music
+            .get(&level.to_string())
+            .or_else(|| self.theme_music.get(self.theme_for_level(level)?))
+            .map(String::as_str)
+    }
+
+    pub fn save(&self) {
+        match toml::to_string_pretty(self) {
+            Ok(content) => {
+                let _ = fs::write(SETTINGS_FILE, content);
+            }
+            Err(err) => println!("⚠️  Could not save settings: {}", err),
+        }
+    }
+}
+
+// Rows shown on the settings screen, in display order
+#[derive(Clone, Copy)]
+enum SettingsItem {
+    RevealMode,
+    RevealAnimation,
+    FitMode,
+    MusicFade,
+    Volume(VolumeChannel),
+    SynthEffects,
+}
+
+const SETTINGS_ITEMS: &[SettingsItem] = &[
+    SettingsItem::RevealMode,
+    SettingsItem::RevealAnimation,
+    SettingsItem::FitMode,
+    SettingsItem::MusicFade,
+    SettingsItem::Volume(VolumeChannel::Master),
+    SettingsItem::Volume(VolumeChannel::Music),
+    SettingsItem::Volume(VolumeChannel::Sfx),
+    SettingsItem::SynthEffects,
+];
+
+impl SettingsItem {
+    fn label(self) -> &'static str {
+        match self {
+            SettingsItem::RevealMode => "Image reveal",
+            SettingsItem::RevealAnimation => "Level complete animation",
+            SettingsItem::FitMode => "Image fit",
+            SettingsItem::MusicFade => "Music crossfade",
+            SettingsItem::Volume(channel) => channel.label(),
+            SettingsItem::SynthEffects => "Synth effects",
+        }
+    }
+
+    fn value(self, settings: &Settings) -> String {
+        match self {
+            SettingsItem::RevealMode => settings.reveal_mode.label().to_string(),
+            SettingsItem::RevealAnimation => settings.reveal_animation.label().to_string(),
+            SettingsItem::FitMode => settings.fit_mode.label().to_string(),
+            SettingsItem::MusicFade if settings.music_fade_seconds <= 0.0 => "Off".to_string(),
+            SettingsItem::MusicFade => format!("{:.1} s", settings.music_fade_seconds),
+            SettingsItem::Volume(channel) => format!("{:.0}%", settings.audio.volume(channel) * 100.0),
+            SettingsItem::SynthEffects => settings.audio.synth_effects.label().to_string(),
+        }
+    }
+
+    fn adjust(self, settings: &mut Settings, step: i32) {
+        match self {
+            SettingsItem::RevealMode => settings.reveal_mode = settings.reveal_mode.cycle(step),
+            SettingsItem::RevealAnimation => {
+                settings.reveal_animation = settings.reveal_animation.cycle(step)
+            }
+            SettingsItem::FitMode => settings.fit_mode = settings.fit_mode.cycle(step),
+            SettingsItem::MusicFade => {
+                // Values typed into settings.toml snap to the nearest offered option
+                let nearest = MUSIC_FADE_OPTIONS
+                    .iter()
+                    .copied()
+                    .min_by(|a, b| {
+                        (a - settings.music_fade_seconds)
+                            .abs()
+                            .total_cmp(&(b - settings.music_fade_seconds).abs())
+                    })
+                    .unwrap_or(0.0);
+                settings.music_fade_seconds = cycle_in(MUSIC_FADE_OPTIONS, nearest, step);
+            }
+            SettingsItem::Volume(channel) => settings.audio.adjust_volume(channel, step),
+            SettingsItem::SynthEffects => {
+                settings.audio.synth_effects = settings.audio.synth_effects.cycle(step)
+            }
+        }
+    }
+}
+
+#[derive(Resource, Default)]
+pub struct SettingsMenu {
+    selected: usize,
+}
+
+pub fn handle_settings_screen(
+    keyboard: Res<ButtonInput<KeyCode>>,
+    mut game_phase: ResMut<GamePhase>,
+    mut settings: ResMut<Settings>,
+    mut menu: ResMut<SettingsMenu>,
+    mut sounds: EventWriter<PlaySfx>,
+) {
+    if *game_phase != GamePhase::Settings {
+        return;
+    }
+
+    if keyboard.just_pressed(KeyCode::ArrowUp) || keyboard.just_pressed(KeyCode::KeyW) {
+        menu.selected = (menu.selected + SETTINGS_ITEMS.len() - 1) % SETTINGS_ITEMS.len();
+        sounds.send(GameSound::MenuMove.into());
+    }
+    if keyboard.just_pressed(KeyCode::ArrowDown) || keyboard.just_pressed(KeyCode::KeyS) {
+        menu.selected = (menu.selected + 1) % SETTINGS_ITEMS.len();
+        sounds.send(GameSound::MenuMove.into());
+    }
+
+    let mut step = 0;
+    if keyboard.just_pressed(KeyCode::ArrowLeft) || keyboard.just_pressed(KeyCode::KeyA) {
+        step -= 1;
+    }
+    if keyboard.just_pressed(KeyCode::ArrowRight) || keyboard.just_pressed(KeyCode::KeyD) {
+        step += 1;
+    }
+    if step != 0 {
+        SETTINGS_ITEMS[menu.selected].adjust(&mut settings, step);
+        settings.save();
+        sounds.send(GameSound::MenuMove.into());
+    }
+
+    if keyboard.just_pressed(KeyCode::Escape) || keyboard.just_pressed(KeyCode::Enter) {
+        *game_phase = GamePhase::HighScoreScreen;
+    }
+}
+
+pub fn settings_screen_text(settings: &Settings, menu: &SettingsMenu) -> String {
+    let mut display = String::from("=== SETTINGS ===\n\n");
+    for (i, item) in SETTINGS_ITEMS.iter().enumerate() {
+        let marker = if i == menu.selected { ">" } else { " " };
+        display.push_str(&format!("{} {}: < {} >\n", marker, item.label(), item.value(settings)));
+    }
+    display.push_str("\nUP/DOWN: Select | LEFT/RIGHT: Change\n");
+    display.push_str("ESC or ENTER: Back");
+    display
+}
@@ -0,0 +1,115 @@
+use rand::seq::SliceRandom;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+
+const SHUFFLE_BAG_FILE: &str = "image_order.toml";
+
+// Bag used when no theme is selected
+const ALL_IMAGES: &str = "all";
+
+// One shuffle bag per theme, saved after every draw so the order carries on across restarts
+#[derive(Default, Serialize, Deserialize)]
+#[serde(default)]
+struct ShuffleBags {
+    bags: BTreeMap<String, ShuffleBag>,
+}
+
+impl ShuffleBags {
+    fn load() -> Self {
+        let Ok(content) = fs::read_to_string(SHUFFLE_BAG_FILE) else {
+            return Self::default();
+        };
+        let mut bags: Self = toml::from_str(&content).unwrap_or_default();
+        // Older saves hold a single bag over all images - carry it over as the "all" bag
+        if bags.bags.is_empty() {
+            if let Ok(bag) = toml::from_str::<ShuffleBag>(&content) {
+                if !bag.known.is_empty() {
+                    bags.bags.insert(ALL_IMAGES.to_string(), bag);
+                }
+            }
+        }
+        bags
+    }
+}
+
+// Next image from the theme's bag (or the bag over all images when theme is None).
+// `available` is the theme's current folder listing.
+pub fn draw_image(
+    theme: Option<&str>,
+    available: &[String],
+    exclude: &[String],
+    rng: &mut impl Rng,
+) -> Option<String> {
+    let mut bags = ShuffleBags::load();
+    let bag = bags.bags.entry(theme.unwrap_or(ALL_IMAGES).to_string()).or_default();
+    let drawn = bag.draw(available, exclude, rng);
+
+    if let Ok(content) = toml::to_string_pretty(&bags) {
+        let _ = fs::write(SHUFFLE_BAG_FILE, content);
+    }
+    drawn
+}
+
+// Order in which a set of images is shown: every image once, in shuffled order, before any repeats
+#[derive(Default, Serialize, Deserialize)]
+#[serde(default)]
+struct ShuffleBag {
+    remaining: Vec<String>, // Still to be shown this round, next first
+    known: Vec<String>, // Every image the bag has been filled from
+    last: Option<String>, // Most recently drawn, kept off the front of the next round
+}
+
+impl ShuffleBag {
+    // Take the next image, skipping excluded ones where possible. Images added since the
+    // last draw join this round at a random position, deleted ones are dropped.
+    fn draw(&mut self, available: &[String], exclude: &[String], rng: &mut impl Rng) -> Option<String> {
+        if available.is_empty() {
+            return None;
+        }
+        self.refresh(available, rng);
+
+        let position = match self.remaining.iter().position(|path| !exclude.contains(path)) {
+            Some(position) => position,
+            None => {
+                // Everything left this round is excluded - start the next round early
+                self.refill(available, rng);
+                self.remaining.iter().position(|path| !exclude.contains(path)).unwrap_or(0)
+            }
+        };
+        let drawn = self.remaining.remove(position);
+        self.last = Some(drawn.clone());
+        if self.remaining.is_empty() {
+            self.refill(available, rng);
+        }
+        Some(drawn)
+    }
+
+    fn refresh(&mut self, available: &[String], rng: &mut impl Rng) {
+        self.remaining.retain(|path| available.contains(path));
+        self.known.retain(|path| available.contains(path));
+        for path in available {
+            if !self.known.contains(path) {
+                self.known.push(path.clone());
+                let position = rng.gen_range(0..=self.remaining.len());
+                self.remaining.insert(position, path.clone());
+            }
+        }
+        if self.remaining.is_empty() {
+            self.refill(available, rng);
+        }
+    }
+
+    // Start a new round with every image, never opening with the one just shown
+    fn refill(&mut self, available: &[String], rng: &mut impl Rng) {
+        self.known = available.to_vec();
+        self.remaining = available.to_vec();
+        self.remaining.shuffle(rng);
+        if self.remaining.len() > 1 && self.remaining.first() == self.last.as_ref() {
+            let swap_with = rng.gen_range(1..self.remaining.len());
+            self.remaining.swap(0, swap_with);
+        }
+        println!("🔀 Shuffled {} images for the next round", self.remaining.len());
+    }
+}
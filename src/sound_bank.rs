@@ -0,0 +1,190 @@
+use bevy::prelude::*;
+use rand::seq::SliceRandom;
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::audio_backend::Voice;
+use crate::audio_manager::AudioManager;
+use crate::settings::SynthEffects;
+use crate::synth::synth_sound;
+
+// Which files play for which game event. Paths are relative to assets/.
+const SOUND_BANK_FILE: &str = "assets/sound_bank.toml";
+
+// Game events that can have a sound
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum GameSound {
+    LineStarted,
+    AreaClaimed,
+    EnemyBounce,
+    LifeLost,
+    LevelComplete,
+    #[allow(dead_code)] // No power-ups in the game yet, but the bank can already hold a sound
+    PowerUp,
+    MenuMove,
+    HighScore,
+}
+
+// Sent by gameplay systems to play an event's sound; the audio thread plays it
+#[derive(Event, Clone, Copy, Debug)]
+pub struct PlaySfx {
+    pub sound: GameSound,
+    pub size: f32, // 0.0 to 1.0 for sounds that vary with play, e.g. the share of the field a capture claimed
+}
+
+impl From<GameSound> for PlaySfx {
+    fn from(sound: GameSound) -> Self {
+        PlaySfx { sound, size: 0.0 }
+    }
+}
+
+// One event's entry in the manifest
+#[derive(Clone, Debug, Deserialize)]
+#[serde(default)]
+struct SoundSet {
+    files: Vec<String>, // Alternatives, one picked at random each time
+    max_voices: usize, // How many of this event's sounds may play at once; extras are skipped
+    volume: f32, // Multiplied with the effects volume
+}
+
+impl Default for SoundSet {
+    fn default() -> Self {
+        SoundSet {
+            files: Vec::new(),
+            max_voices: 2,
+            volume: 1.0,
+        }
+    }
+}
+
+// Without a manifest the game keeps its original beep for losing a life
+fn default_manifest() -> HashMap<GameSound, SoundSet> {
+    HashMap::from([(
+        GameSound::LifeLost,
+        SoundSet {
+            files: vec!["negative_beeps-6008.mp3".to_string()],
+            max_voices: 1,
+            ..default()
+        },
+    )])
+}
+
+// Lives on the audio thread
+#[derive(Default)]
+pub struct SoundBank {
+    sounds: HashMap<GameSound, (SoundSet, Vec<PathBuf>)>, // Manifest entry and the files that exist
+    playing: HashMap<GameSound, Vec<Box<dyn Voice>>>,
+}
+
+impl SoundBank {
+    pub fn load() -> Self {
+        let manifest = match fs::read_to_string(SOUND_BANK_FILE) {
+            Ok(content) => toml::from_str(&content).unwrap_or_else(|err| {
+                println!("⚠️  Ignoring invalid {}: {}", SOUND_BANK_FILE, err);
+                default_manifest()
+            }),
+            Err(_) => default_manifest(),
+        };
+
+        // Missing files are dropped now so events fall back to silence instead of failing later
+        let mut sounds = HashMap::new();
+        for (event, set) in manifest {
+            let files: Vec<PathBuf> = set
+                .files
+                .iter()
+                .map(|file| Path::new("assets").join(file))
+                .filter(|path| {
+                    let exists = path.is_file();
+                    if !exists {
+                        println!("⚠️  Sound for {:?} not found: {}", event, path.display());
+                    }
+                    exists
+                })
+                .collect();
+            sounds.insert(event, (set, files));
+        }
+
+        SoundBank {
+            sounds,
+            playing: HashMap::new(),
+        }
+    }
+
+    // Play an event's sound from the bank's files or the synth
+    pub fn play(&mut self, sfx: PlaySfx, audio: &AudioManager, synth: SynthEffects) {
+        let PlaySfx { sound, size } = sfx;
+        // Events missing from the manifest can still be synthesised, with the default limits
+        let (set, files) = self.sounds.entry(sound).or_insert_with(|| (SoundSet::default(), Vec::new()));
+        let voices = self.playing.entry(sound).or_default();
+        if voices.len() >= set.max_voices {
+            return;
+        }
+
+        let file = match synth {
+            SynthEffects::Always => None,
+            _ => files.choose(&mut rand::thread_rng()).cloned(),
+        };
+        let Some(file) = file else {
+            if synth != SynthEffects::Off {
+                let label = format!("{:?} (synth)", sound);
+                if let Ok(Some(voice)) = audio.start_synth_effect(synth_sound(sound, size), &label, set.volume) {
+                    voices.push(voice);
+                }
+            }
+            return;
+        };
+
+        match audio.start_sound_effect(&file, set.volume) {
+            Ok(Some(voice)) => voices.push(voice),
+            Ok(None) => {} // Muted
+            Err(err) => {
+                // Don't try an undecodable file again
+                println!("⚠️  Cannot play {}: {}", file.display(), err);
+                files.retain(|other| *other != file);
+            }
+        }
+    }
+
+    // Forget sounds that have finished, and hold the rest while the game is paused
+    pub fn update(&mut self, hold: bool) {
+        for voices in self.playing.values_mut() {
+            voices.retain(|voice| !voice.empty());
+            for voice in voices.iter() {
+                if hold {
+                    voice.pause();
+                } else {
+                    voice.play();
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::audio_backend::SilentBackend;
+    use crate::settings::AudioSettings;
+
+    #[test]
+    fn play_respects_max_voices() {
+        let audio = AudioManager::new(Box::<SilentBackend>::default(), Vec::new(), &AudioSettings::default());
+        let mut bank = SoundBank::default();
+        bank.sounds.insert(
+            GameSound::AreaClaimed,
+            (SoundSet { max_voices: 1, ..default() }, Vec::new()),
+        );
+
+        for _ in 0..3 {
+            bank.play(GameSound::AreaClaimed.into(), &audio, SynthEffects::Always);
+            bank.play(GameSound::LineStarted.into(), &audio, SynthEffects::Always);
+        }
+
+        assert_eq!(bank.playing[&GameSound::AreaClaimed].len(), 1);
+        // Events missing from the manifest get the default limit
+        assert_eq!(bank.playing[&GameSound::LineStarted].len(), SoundSet::default().max_voices);
+    }
+}
@@ -0,0 +1,173 @@
+use rodio::source::{from_iter, SineWave, Source};
+use std::f32::consts::PI;
+use std::time::Duration;
+
+use crate::audio_backend::Sound;
+use crate::sound_bank::GameSound;
+
+// Same rate as rodio's SineWave so mixed sounds don't need resampling
+const SAMPLE_RATE: u32 = 48_000;
+
+// C major arpeggio, in semitones above the root
+const ARPEGGIO: [f32; 8] = [0.0, 4.0, 7.0, 12.0, 16.0, 19.0, 24.0, 28.0];
+
+
+// Sine tone gliding from one pitch to another (exponentially, so it sounds even)
+struct Sweep {
+    from: f32,
+    to: f32,
+    samples: u32,
+    sample: u32,
+    phase: f32,
+}
+
+impl Sweep {
+    fn new(from: f32, to: f32, length: Duration) -> Self {
+        Sweep {
+            from,
+            to,
+            samples: (length.as_secs_f32() * SAMPLE_RATE as f32) as u32,
+            sample: 0,
+            phase: 0.0,
+        }
+    }
+}
+
+impl Iterator for Sweep {
+    type Item = f32;
+
+    fn next(&mut self) -> Option<f32> {
+        if self.sample >= self.samples {
+            return None;
+        }
+        let progress = self.sample as f32 / self.samples as f32;
+        let frequency = self.from * (self.to / self.from).powf(progress);
+        // Accumulate phase so the pitch can change without clicks
+        self.phase = (self.phase + frequency / SAMPLE_RATE as f32).fract();
+        self.sample += 1;
+        Some((self.phase * 2.0 * PI).sin())
+    }
+}
+
+impl Source for Sweep {
+    fn current_frame_len(&self) -> Option<usize> {
+        None
+    }
+
+    fn channels(&self) -> u16 {
+        1
+    }
+
+    fn sample_rate(&self) -> u32 {
+        SAMPLE_RATE
+    }
+
+    fn total_duration(&self) -> Option<Duration> {
+        Some(Duration::from_secs_f32(self.samples as f32 / SAMPLE_RATE as f32))
+    }
+}
+
+// Endless white noise (xorshift), cut to length with take_duration
+struct Noise {
+    state: u32,
+}
+
+impl Iterator for Noise {
+    type Item = f32;
+
+    fn next(&mut self) -> Option<f32> {
+        self.state ^= self.state << 13;
+        self.state ^= self.state >> 17;
+        self.state ^= self.state << 5;
+        Some(self.state as f32 / u32::MAX as f32 * 2.0 - 1.0)
+    }
+}
+
+impl Source for Noise {
+    fn current_frame_len(&self) -> Option<usize> {
+        None
+    }
+
+    fn channels(&self) -> u16 {
+        1
+    }
+
+    fn sample_rate(&self) -> u32 {
+        SAMPLE_RATE
+    }
+
+    fn total_duration(&self) -> Option<Duration> {
+        None
+    }
+}
+
+fn ms(milliseconds: u64) -> Duration {
+    Duration::from_millis(milliseconds)
+}
+
+// Short attack, then a fade out over the whole length
+fn envelope<S>(source: S, attack: Duration, length: Duration) -> impl Source<Item = f32> + Send
+where
+    S: Source<Item = f32> + Send,
+{
+    let mut shaped = source.fade_in(attack).take_duration(length);
+    shaped.set_filter_fadeout();
+    shaped
+}
+
+fn note(frequency: f32, length: Duration) -> impl Source<Item = f32> + Send {
+    envelope(SineWave::new(frequency), ms(5), length)
+}
+
+fn noise_burst(length: Duration, cutoff: u32) -> impl Source<Item = f32> + Send {
+    envelope(Noise { state: 0x9E37_79B9 }, ms(2), length).low_pass(cutoff)
+}
+
+// Notes of the arpeggio played one after another
+fn arpeggio(root: f32, notes: usize, note_length: Duration) -> impl Source<Item = f32> + Send {
+    let notes: Vec<_> = ARPEGGIO
+        .iter()
+        .cycle()
+        .take(notes)
+        .enumerate()
+        .map(|(i, semitones)| {
+            // Past the table's end, carry on two octaves up
+            let octaves = (i / ARPEGGIO.len()) as f32 * 2.0;
+            note(root * 2f32.powf(semitones / 12.0 + octaves), note_length)
+        })
+        .collect();
+    from_iter(notes)
+}
+
+// Generated sound for a game event. `size` (0.0 to 1.0) scales sounds that depend on play,
+// e.g. the share of the playfield a capture claimed.
+pub fn synth_sound(sound: GameSound, size: f32) -> Sound {
+    let size = size.clamp(0.0, 1.0);
+    match sound {
+        GameSound::LineStarted => Box::new(envelope(Sweep::new(300.0, 600.0, ms(90)), ms(5), ms(90)).amplify(0.25)),
+        GameSound::AreaClaimed => {
+            // Bigger captures get more notes and start lower: up to an octave down at a quarter of the field
+            let notes = 3 + (size * 40.0).round().min(9.0) as usize;
+            let root = 523.25 * 2f32.powf(-(size * 4.0).min(1.0));
+            Box::new(arpeggio(root, notes, ms(55)).amplify(0.3))
+        }
+        GameSound::EnemyBounce => Box::new(noise_burst(ms(30), 1500).amplify(0.6)),
+        GameSound::LifeLost => Box::new(
+            envelope(Sweep::new(600.0, 110.0, ms(650)), ms(10), ms(650))
+                .mix(noise_burst(ms(200), 800))
+                .amplify(0.35),
+        ),
+        GameSound::LevelComplete => Box::new(
+            arpeggio(392.0, 4, ms(110))
+                .mix(note(784.0, ms(700)).delay(ms(440)))
+                .amplify(0.3),
+        ),
+        GameSound::PowerUp => Box::new(
+            envelope(Sweep::new(300.0, 1200.0, ms(250)), ms(5), ms(250))
+                .mix(envelope(Sweep::new(450.0, 1800.0, ms(250)), ms(5), ms(250)).delay(ms(120)))
+                .amplify(0.2),
+        ),
+        GameSound::MenuMove => Box::new(note(1200.0, ms(25)).amplify(0.15)),
+        GameSound::HighScore => Box::new(arpeggio(523.25, 12, ms(70)).amplify(0.3)),
+    }
+}